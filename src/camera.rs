@@ -1,5 +1,24 @@
-use raylib::prelude::*;
+use crate::math::Vector3;
+use std::f32::consts::PI;
 
+// Campo de visión vertical por defecto (60°)
+pub const DEFAULT_FOV: f32 = PI / 3.0;
+
+// Cómo se proyecta la escena sobre la pantalla
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    Perspective { fov: f32 },          // ángulo vertical en radianes
+    Orthographic { height: f32 },      // alto de la vista en unidades del mundo
+    Isometric { height: f32 },         // ortográfica con el ojo en una diagonal (ver with_isometric)
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Projection::Perspective { fov: DEFAULT_FOV }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct Camera {
     pub eye: Vector3, //donde esta la camara en el mundo
     pub center: Vector3, //que mira la camara 
@@ -7,6 +26,8 @@ pub struct Camera {
 
     pub forward: Vector3,
     pub right: Vector3,
+
+    pub projection: Projection,
 }
 
 impl Camera {
@@ -17,12 +38,42 @@ impl Camera {
             up,
             forward: Vector3::zero(),
             right: Vector3::zero(),
+            projection: Projection::default(),
         };
 
         camera.update_basis();
         camera
     }
 
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    // Vista isométrica: ortográfica, mirando al centro por la diagonal (±1, 1, ±1) del
+    // cuadrante donde estaba el ojo y a la misma distancia
+    pub fn with_isometric(mut self, view_height: f32) -> Self {
+        let offset = self.eye - self.center;
+        let diagonal = Vector3::new(
+            if offset.x < 0.0 { -1.0 } else { 1.0 },
+            1.0,
+            if offset.z < 0.0 { -1.0 } else { 1.0 },
+        );
+        self.eye = self.center + diagonal.normalized() * offset.length();
+        self.up = Vector3::new(0.0, 1.0, 0.0);
+        self.projection = Projection::Isometric { height: view_height };
+        self.update_basis();
+        self
+    }
+
+    // Alto de vista ortográfica que encuadra en el centro lo mismo que la proyección actual
+    pub fn view_height_at_center(&self) -> f32 {
+        match self.projection {
+            Projection::Perspective { fov } => 2.0 * (self.center - self.eye).length() * (fov * 0.5).tan(),
+            Projection::Orthographic { height } | Projection::Isometric { height } => height,
+        }
+    }
+
     pub fn update_basis(&mut self) {
         self.forward = (self.center - self.eye).normalized();
         self.right = self.forward.cross(self.up).normalized();
@@ -65,5 +116,60 @@ impl Camera {
             p.x * self.right.z + p.y * self.up.z - p.z * self.forward.z,
         )
     }
+
+    // Rayo (origen, dirección) para un punto de la pantalla con x, y en [-1, 1]
+    pub fn ray(&self, screen_x: f32, screen_y: f32, aspect_ratio: f32) -> (Vector3, Vector3) {
+        match self.projection {
+            Projection::Perspective { fov } => {
+                let scale = (fov * 0.5).tan();
+                let direction = Vector3::new(screen_x * aspect_ratio * scale, screen_y * scale, -1.0).normalized();
+                (self.eye, self.basis_change(&direction))
+            }
+            // todos los rayos paralelos, saliendo del plano del ojo
+            Projection::Orthographic { height } | Projection::Isometric { height } => {
+                let half = height * 0.5;
+                let offset = Vector3::new(screen_x * aspect_ratio * half, screen_y * half, 0.0);
+                (self.eye + self.basis_change(&offset), self.forward)
+            }
+        }
+    }
     
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera() -> Camera {
+        Camera::new(Vector3::new(4.0, 3.0, 5.0), Vector3::zero(), Vector3::new(0.0, 1.0, 0.0))
+    }
+
+    #[test]
+    fn perspective_rays_fan_out_from_the_eye() {
+        let camera = camera();
+        let (origin, direction) = camera.ray(0.0, 0.0, 1.0);
+        assert_eq!(origin, camera.eye);
+        assert!((direction - camera.forward).length() < 1e-5);
+
+        // en el borde de arriba el rayo sube la mitad del campo de visión
+        let (_, top) = camera.ray(0.0, 1.0, 1.0);
+        assert!((top.dot(camera.forward).acos() - DEFAULT_FOV * 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn isometric_rays_are_parallel_along_the_diagonal() {
+        let camera = camera().with_isometric(4.0);
+        let diagonal = -Vector3::new(1.0, 1.0, 1.0).normalized();
+        let (center_origin, center_direction) = camera.ray(0.0, 0.0, 1.5);
+        assert!((center_direction - diagonal).length() < 1e-5);
+        assert_eq!(center_origin, camera.eye);
+
+        // la esquina sale del plano del ojo, a medio alto (y medio ancho por el aspecto) del centro
+        let (corner_origin, corner_direction) = camera.ray(1.0, 1.0, 1.5);
+        assert_eq!(corner_direction, center_direction);
+        let offset = corner_origin - center_origin;
+        assert!(offset.dot(diagonal).abs() < 1e-5);
+        assert!((offset.dot(camera.up) - 2.0).abs() < 1e-4);
+        assert!((offset.dot(camera.right) - 3.0).abs() < 1e-4);
+    }
+}
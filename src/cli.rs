@@ -0,0 +1,252 @@
+// cli.rs
+use raytracer::camera::{Camera, Projection};
+use raytracer::math::Vector3;
+
+pub const USAGE: &str = "\
+Uso: Proyecto2_Raytracer [opciones]
+
+Sin opciones abre la ventana interactiva. Con --headless (o --output) renderiza
+una sola imagen a PNG sin abrir ventana. Si se compiló sin la feature `window`
+solo está disponible el modo sin ventana.
+
+Opciones:
+  --scene <archivo>      escena TOML a cargar (por defecto assets/scenes/diorama.toml)
+  --headless             renderiza sin ventana
+  --output <archivo>     PNG de salida (por defecto render.png; implica --headless)
+  --width <px>           ancho del framebuffer (por defecto 1000, máximo 16384)
+  --height <px>          alto del framebuffer (por defecto 900, máximo 16384)
+  --eye <x,y,z>          posición de la cámara
+  --center <x,y,z>       punto al que mira la cámara
+  --up <x,y,z>           vector arriba de la cámara
+  --fov <grados>         campo de visión vertical de la perspectiva (por defecto 60)
+  --ortho <alto>         proyección ortográfica con una vista de <alto> unidades
+  --isometric            vista isométrica (ortográfica; el alto sale de --ortho o de la escena)
+  --time <segundos>      instante del reloj para materiales animados (por defecto 0)
+  --export <archivo>     exporta la escena a .obj (+ .mtl) o .glb en vez de renderizar
+  --cull                 al exportar, quita las caras tapadas entre cubos vecinos
+  -h, --help             muestra esta ayuda";
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub scene: String,
+    pub headless: bool,
+    pub output: String,
+    pub width: u32,
+    pub height: u32,
+    pub eye: Option<Vector3>,
+    pub center: Option<Vector3>,
+    pub up: Option<Vector3>,
+    pub fov: Option<f32>,
+    pub ortho: Option<f32>,
+    pub isometric: bool,
+    pub time: f32,
+    pub export: Option<String>,
+    pub cull: bool,
+    pub help: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            scene: "assets/scenes/diorama.toml".to_string(),
+            headless: false,
+            output: "render.png".to_string(),
+            width: 1000,
+            height: 900,
+            eye: None,
+            center: None,
+            up: None,
+            fov: None,
+            ortho: None,
+            isometric: false,
+            time: 0.0,
+            export: None,
+            cull: false,
+            help: false,
+        }
+    }
+}
+
+impl Options {
+    // `args` sin el nombre del programa
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = |flag: &str| args.next().ok_or_else(|| format!("{} needs a value", flag));
+            match arg.as_str() {
+                "--scene" => options.scene = value(&arg)?,
+                "--headless" => options.headless = true,
+                "--output" | "-o" => {
+                    options.output = value(&arg)?;
+                    options.headless = true;
+                }
+                "--width" => options.width = parse_size(&arg, &value(&arg)?)?,
+                "--height" => options.height = parse_size(&arg, &value(&arg)?)?,
+                "--eye" => options.eye = Some(parse_vector3(&arg, &value(&arg)?)?),
+                "--center" => options.center = Some(parse_vector3(&arg, &value(&arg)?)?),
+                "--up" => options.up = Some(parse_vector3(&arg, &value(&arg)?)?),
+                "--fov" => match parse_f32(&arg, &value(&arg)?)? {
+                    fov if fov > 0.0 && fov < 180.0 => options.fov = Some(fov),
+                    fov => return Err(format!("--fov: must be between 0 and 180 degrees (got {})", fov)),
+                },
+                "--ortho" => match parse_f32(&arg, &value(&arg)?)? {
+                    height if height > 0.0 => options.ortho = Some(height),
+                    height => return Err(format!("--ortho: must be positive (got {})", height)),
+                },
+                "--isometric" => options.isometric = true,
+                "--time" => options.time = parse_f32(&arg, &value(&arg)?)?,
+                "--export" => options.export = Some(value(&arg)?),
+                "--cull" => options.cull = true,
+                "-h" | "--help" => options.help = true,
+                other => return Err(format!("unknown option '{}'", other)),
+            }
+        }
+        if options.fov.is_some() && (options.ortho.is_some() || options.isometric) {
+            return Err("--fov cannot be combined with --ortho or --isometric".to_string());
+        }
+        Ok(options)
+    }
+
+    // Aplica --eye/--center/--up, --fov, --ortho e --isometric a la cámara de la escena
+    pub fn camera(&self, scene_camera: Camera) -> Camera {
+        let mut camera = scene_camera;
+        if self.eye.is_some() || self.center.is_some() || self.up.is_some() {
+            let moved = Camera::new(
+                self.eye.unwrap_or(camera.eye),
+                self.center.unwrap_or(camera.center),
+                self.up.unwrap_or(camera.up),
+            );
+            // la isométrica se vuelve a ajustar a la diagonal desde el ojo nuevo
+            camera = match camera.projection {
+                Projection::Isometric { height } => moved.with_isometric(height),
+                projection => moved.with_projection(projection),
+            };
+        }
+        if let Some(fov) = self.fov {
+            camera = camera.with_projection(Projection::Perspective { fov: fov.to_radians() });
+        }
+        if self.isometric {
+            let view_height = self.ortho.unwrap_or_else(|| camera.view_height_at_center());
+            camera = camera.with_isometric(view_height);
+        } else if let Some(height) = self.ortho {
+            // --ortho solo cambia el alto: una escena isométrica sigue siéndolo
+            camera = match camera.projection {
+                Projection::Isometric { .. } => camera.with_projection(Projection::Isometric { height }),
+                _ => camera.with_projection(Projection::Orthographic { height }),
+            };
+        }
+        camera
+    }
+}
+
+// Lado máximo del framebuffer: 16384² píxeles ya son ~2 GB entre color y luz
+pub const MAX_SIZE: u32 = 16384;
+
+// `f32::from_str` acepta "nan" e "inf"; ninguna opción numérica tiene sentido con ellos
+fn parse_f32(flag: &str, text: &str) -> Result<f32, String> {
+    match text.trim().parse::<f32>() {
+        Ok(number) if number.is_finite() => Ok(number),
+        _ => Err(format!("{}: '{}' is not a finite number", flag, text)),
+    }
+}
+
+fn parse_size(flag: &str, text: &str) -> Result<u32, String> {
+    match text.trim().parse::<u32>() {
+        Ok(size) if size > MAX_SIZE => Err(format!("{}: {} is too large (at most {})", flag, size, MAX_SIZE)),
+        Ok(size) if size > 0 => Ok(size),
+        _ => Err(format!("{}: '{}' is not a positive integer", flag, text)),
+    }
+}
+
+fn parse_vector3(flag: &str, text: &str) -> Result<Vector3, String> {
+    let parts: Vec<&str> = text.split(',').collect();
+    if parts.len() != 3 {
+        return Err(format!("{}: expected x,y,z but got '{}'", flag, text));
+    }
+    Ok(Vector3::new(
+        parse_f32(flag, parts[0])?,
+        parse_f32(flag, parts[1])?,
+        parse_f32(flag, parts[2])?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn scene_camera() -> Camera {
+        Camera::new(Vector3::new(0.0, 2.0, 10.0), Vector3::zero(), Vector3::new(0.0, 1.0, 0.0))
+    }
+
+    fn on_diagonal(camera: &Camera, sign_x: f32, sign_z: f32) -> bool {
+        let direction = (camera.eye - camera.center).normalized();
+        (direction - Vector3::new(sign_x, 1.0, sign_z).normalized()).length() < 1e-5
+    }
+
+    #[test]
+    fn eye_on_an_isometric_scene_snaps_back_to_the_diagonal() {
+        let isometric = scene_camera().with_isometric(6.0);
+        let camera = parse(&["--eye", "-4,1,3"]).unwrap().camera(isometric);
+        assert_eq!(camera.projection, Projection::Isometric { height: 6.0 });
+        assert!(on_diagonal(&camera, -1.0, 1.0), "{:?}", camera.eye);
+        assert!((camera.eye.length() - Vector3::new(-4.0, 1.0, 3.0).length()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn ortho_keeps_an_isometric_scene_isometric() {
+        let isometric = scene_camera().with_isometric(6.0);
+        let camera = parse(&["--ortho", "3"]).unwrap().camera(isometric);
+        assert_eq!(camera.projection, Projection::Isometric { height: 3.0 });
+        assert_eq!(camera.eye, isometric.eye);
+
+        let camera = parse(&["--ortho", "3"]).unwrap().camera(scene_camera());
+        assert_eq!(camera.projection, Projection::Orthographic { height: 3.0 });
+        assert_eq!(camera.eye, scene_camera().eye);
+    }
+
+    #[test]
+    fn isometric_flag_takes_its_height_from_ortho_or_the_scene() {
+        let camera = parse(&["--isometric", "--ortho", "5"]).unwrap().camera(scene_camera());
+        assert_eq!(camera.projection, Projection::Isometric { height: 5.0 });
+        assert!(on_diagonal(&camera, 1.0, 1.0));
+
+        // sin --ortho encuadra en el centro lo mismo que la perspectiva de la escena
+        let expected = scene_camera().view_height_at_center();
+        let camera = parse(&["--isometric"]).unwrap().camera(scene_camera());
+        assert_eq!(camera.projection, Projection::Isometric { height: expected });
+    }
+
+    #[test]
+    fn fov_turns_any_scene_back_to_perspective_but_not_with_ortho() {
+        let ortho = scene_camera().with_projection(Projection::Orthographic { height: 4.0 });
+        let camera = parse(&["--fov", "90"]).unwrap().camera(ortho);
+        assert_eq!(camera.projection, Projection::Perspective { fov: 90f32.to_radians() });
+
+        assert!(parse(&["--fov", "90", "--ortho", "4"]).is_err());
+        assert!(parse(&["--ortho", "4", "--isometric", "--fov", "90"]).is_err());
+    }
+
+    #[test]
+    fn rejects_absurd_sizes() {
+        assert!(parse(&["--width", "16384", "--height", "16384"]).is_ok());
+        let error = parse(&["--width", "70000", "--height", "70000"]).err().unwrap();
+        assert!(error.contains("--width") && error.contains("too large"), "{}", error);
+        assert!(parse(&["--height", "0"]).is_err());
+    }
+
+    #[test]
+    fn rejects_non_finite_numbers() {
+        for text in ["nan", "inf", "-inf"] {
+            let error = parse(&["--time", text]).err().unwrap();
+            assert!(error.contains("--time"), "{}", error);
+        }
+        assert!(parse(&["--eye", "0,nan,1"]).is_err());
+        assert_eq!(parse(&["--time", "2.5"]).unwrap().time, 2.5);
+    }
+}
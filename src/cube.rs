@@ -1,14 +1,13 @@
 // cube.rs
-#[warn(unused_assignments)]
-
 use crate::ray_intersect::{RayIntersect, Intersect};
-use crate::material::Material;
-use raylib::prelude::{Vector2, Vector3}; // <-- Añade Vector2
+use crate::material::MaterialId;
+use crate::math::{Vector2, Vector3}; // <-- Añade Vector2
 
+#[derive(Debug, Clone, Copy)]
 pub struct Cube {
     pub center: Vector3,
     pub size: f32,
-    pub material: Material,
+    pub material_id: MaterialId,
 }
 
 impl RayIntersect for Cube {
@@ -59,18 +58,23 @@ impl RayIntersect for Cube {
         }
 
         // Comprobación final de tmax
-        // if tzmax < tmax {
-        //     tmax = tzmax;
-        // }
+        if tzmax < tmax {
+            tmax = tzmax;
+        }
 
+        // Si el origen está dentro del cubo, el impacto es la cara de salida
         if tmin < 0.0 {
-            return Intersect::empty();
+            if tmax < 0.0 {
+                return Intersect::empty();
+            }
+            tmin = tmax;
         }
 
         let point = *ray_origin + *ray_direction * tmin;
         let p_local = point - self.center; // Punto relativo al centro del cubo
 
-        let (normal, uv) = {
+        // tangente/bitangente = hacia dónde crecen u y v en la cara (para normal maps)
+        let (normal, uv, tangent, bitangent) = {
             let abs_p = Vector3::new(p_local.x.abs(), p_local.y.abs(), p_local.z.abs());
             
             if abs_p.x > abs_p.y && abs_p.x > abs_p.z { // Cara X
@@ -80,7 +84,7 @@ impl RayIntersect for Cube {
                     (p_local.z * sign + half_size) / self.size,
                     (p_local.y + half_size) / self.size,
                 );
-                (n, uv)
+                (n, uv, Vector3::new(0.0, 0.0, sign), Vector3::new(0.0, 1.0, 0.0))
             } else if abs_p.y > abs_p.z { // Cara Y
                 let sign = p_local.y.signum();
                 let n = Vector3::new(0.0, sign, 0.0);
@@ -88,7 +92,7 @@ impl RayIntersect for Cube {
                     (p_local.x + half_size) / self.size,
                     (p_local.z * -sign + half_size) / self.size,
                 );
-                (n, uv)
+                (n, uv, Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -sign))
             } else { // Cara Z
                 let sign = p_local.z.signum();
                 let n = Vector3::new(0.0, 0.0, sign);
@@ -96,7 +100,7 @@ impl RayIntersect for Cube {
                     (p_local.x * -sign + half_size) / self.size,
                     (p_local.y + half_size) / self.size,
                 );
-                (n, uv)
+                (n, uv, Vector3::new(-sign, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0))
             }
         };
 
@@ -105,8 +109,48 @@ impl RayIntersect for Cube {
             is_intersecting: true,
             point,
             normal,
-            material: self.material,
+            material_id: self.material_id,
             uv: Some(uv),
+            tangent,
+            bitangent,
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cube() -> Cube {
+        Cube { center: Vector3::zero(), size: 2.0, material_id: 3 }
+    }
+
+    #[test]
+    fn hits_the_facing_side() {
+        let hit = cube().ray_intersect(&Vector3::new(0.5, 0.0, 5.0), &Vector3::new(0.0, 0.0, -1.0));
+        assert!(hit.is_intersecting);
+        assert_eq!(hit.distance, 4.0);
+        assert_eq!(hit.point, Vector3::new(0.5, 0.0, 1.0));
+        assert_eq!(hit.normal, Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!(hit.material_id, 3);
+        assert_eq!(hit.uv, Some(Vector2::new(0.25, 0.5))); // u crece hacia -x en la cara +z
+    }
+
+    #[test]
+    fn misses_beside_and_behind() {
+        let beside = cube().ray_intersect(&Vector3::new(3.0, 0.0, 5.0), &Vector3::new(0.0, 0.0, -1.0));
+        assert!(!beside.is_intersecting);
+
+        // el cubo queda detrás del origen
+        let behind = cube().ray_intersect(&Vector3::new(0.0, 0.0, 5.0), &Vector3::new(0.0, 0.0, 1.0));
+        assert!(!behind.is_intersecting);
+    }
+
+    #[test]
+    fn origin_inside_hits_the_exit_face() {
+        let hit = cube().ray_intersect(&Vector3::new(0.0, 0.5, 0.0), &Vector3::new(1.0, 0.0, 0.0));
+        assert!(hit.is_intersecting);
+        assert_eq!(hit.distance, 1.0);
+        assert_eq!(hit.point, Vector3::new(1.0, 0.5, 0.0));
+        assert_eq!(hit.normal, Vector3::new(1.0, 0.0, 0.0)); // normal hacia afuera
+    }
+}
@@ -0,0 +1,577 @@
+// export.rs
+// Exporta los primitivos de una escena a Wavefront OBJ + MTL o a glTF 2.0 binario (.glb)
+// para abrirlos en Blender o en un visor web. Los cubos salen con las mismas UV que usa
+// el render; las esferas se teselan. La uv_transform del material se aplica en t = 0 (sin
+// animación). Las texturas se referencian por la ruta con la que se cargaron (las
+// procedurales no se exportan).
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::{Component, Path, PathBuf};
+
+use crate::cube::Cube;
+use crate::material::{Material, MaterialId, ShadingModel, UvTransform};
+use crate::math::{Vector2, Vector3};
+use crate::primitive::Primitive;
+use crate::scene::Scene;
+use crate::sphere::Sphere;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExportOptions {
+    pub cull_hidden_faces: bool,     // quita caras tapadas por un cubo vecino del mismo tamaño
+}
+
+// Triángulos de un material
+#[derive(Default)]
+struct Mesh {
+    positions: Vec<Vector3>,
+    normals: Vec<Vector3>,
+    uvs: Vec<Vector2>,               // v hacia arriba, como en el render
+    indices: Vec<u32>,
+}
+
+impl Mesh {
+    fn push_vertex(&mut self, position: Vector3, normal: Vector3, uv: Vector2) -> u32 {
+        self.positions.push(position);
+        self.normals.push(normal);
+        self.uvs.push(uv);
+        (self.positions.len() - 1) as u32
+    }
+}
+
+const SPHERE_SEGMENTS: u32 = 24;
+const SPHERE_RINGS: u32 = 16;
+
+// Agrupa todos los triángulos de la escena por material
+fn build_meshes(scene: &Scene, options: &ExportOptions) -> BTreeMap<MaterialId, Mesh> {
+    let materials = scene.materials.materials();
+
+    // cubos por posición para saber qué caras quedan tapadas
+    let key = |center: Vector3, size: f32| {
+        let q = |v: f32| (v * 1000.0).round() as i64;
+        (q(center.x), q(center.y), q(center.z), q(size))
+    };
+    let mut occupied: HashMap<(i64, i64, i64, i64), MaterialId> = HashMap::new();
+    if options.cull_hidden_faces {
+        for object in &scene.objects {
+            if let Primitive::Cube(cube) = object {
+                occupied.insert(key(cube.center, cube.size), cube.material_id);
+            }
+        }
+    }
+    // una cara se tapa con un vecino opaco o del mismo material (agua con agua)
+    let is_hidden = |cube: &Cube, normal: Vector3| {
+        occupied
+            .get(&key(cube.center + normal * cube.size, cube.size))
+            .is_some_and(|neighbor| *neighbor == cube.material_id || is_opaque(&materials[*neighbor as usize]))
+    };
+
+    let mut meshes: BTreeMap<MaterialId, Mesh> = BTreeMap::new();
+    for object in &scene.objects {
+        match object {
+            Primitive::Cube(cube) => {
+                let mesh = meshes.entry(cube.material_id).or_default();
+                let uv_transform = &materials[cube.material_id as usize].uv_transform;
+                for (normal, tangent, bitangent) in CUBE_FACES {
+                    if options.cull_hidden_faces && is_hidden(cube, normal) {
+                        continue;
+                    }
+                    add_cube_face(mesh, cube, normal, tangent, bitangent, uv_transform);
+                }
+            }
+            Primitive::Sphere(sphere) => {
+                let uv_transform = &materials[sphere.material_id as usize].uv_transform;
+                add_sphere(meshes.entry(sphere.material_id).or_default(), sphere, uv_transform);
+            }
+        }
+    }
+    meshes
+}
+
+fn is_opaque(material: &Material) -> bool {
+    material.transparency == 0.0 && material.alpha_cutoff == 0.0
+}
+
+// (normal, tangente, bitangente) de cada cara, igual que en Cube::ray_intersect: u crece
+// hacia la tangente y v hacia la bitangente
+const CUBE_FACES: [(Vector3, Vector3, Vector3); 6] = [
+    (Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.0, 1.0, 0.0)),
+    (Vector3::new(-1.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0), Vector3::new(0.0, 1.0, 0.0)),
+    (Vector3::new(0.0, 1.0, 0.0), Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0)),
+    (Vector3::new(0.0, -1.0, 0.0), Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 1.0)),
+    (Vector3::new(0.0, 0.0, 1.0), Vector3::new(-1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0)),
+    (Vector3::new(0.0, 0.0, -1.0), Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0)),
+];
+
+fn add_cube_face(
+    mesh: &mut Mesh,
+    cube: &Cube,
+    normal: Vector3,
+    tangent: Vector3,
+    bitangent: Vector3,
+    uv_transform: &UvTransform,
+) {
+    let half = cube.size * 0.5;
+    let center = cube.center + normal * half;
+    let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+
+    let mut quad = [0u32; 4];
+    for (i, (su, sv)) in corners.into_iter().enumerate() {
+        let position = center + tangent * (su * half) + bitangent * (sv * half);
+        let uv = uv_transform.apply(Vector2::new((su + 1.0) * 0.5, (sv + 1.0) * 0.5), 0.0);
+        quad[i] = mesh.push_vertex(position, normal, uv);
+    }
+
+    // triángulos en sentido antihorario vistos desde fuera
+    if tangent.cross(bitangent).dot(normal) > 0.0 {
+        mesh.indices.extend_from_slice(&[quad[0], quad[1], quad[2], quad[0], quad[2], quad[3]]);
+    } else {
+        mesh.indices.extend_from_slice(&[quad[0], quad[2], quad[1], quad[0], quad[3], quad[2]]);
+    }
+}
+
+fn add_sphere(mesh: &mut Mesh, sphere: &Sphere, uv_transform: &UvTransform) {
+    let first = mesh.positions.len() as u32;
+    for ring in 0..=SPHERE_RINGS {
+        let v = ring as f32 / SPHERE_RINGS as f32;
+        let (sin_theta, cos_theta) = (v * std::f32::consts::PI).sin_cos();
+        for segment in 0..=SPHERE_SEGMENTS {
+            let u = segment as f32 / SPHERE_SEGMENTS as f32;
+            let (sin_phi, cos_phi) = (u * 2.0 * std::f32::consts::PI).sin_cos();
+            let normal = Vector3::new(sin_theta * cos_phi, cos_theta, -sin_theta * sin_phi);
+            let uv = uv_transform.apply(Vector2::new(u, 1.0 - v), 0.0);
+            mesh.push_vertex(sphere.center + normal * sphere.radius, normal, uv);
+        }
+    }
+
+    let stride = SPHERE_SEGMENTS + 1;
+    for ring in 0..SPHERE_RINGS {
+        for segment in 0..SPHERE_SEGMENTS {
+            let a = first + ring * stride + segment;
+            let b = a + stride;
+            // en los polos uno de los dos triángulos del cuadro colapsa a una línea
+            if ring != 0 {
+                mesh.indices.extend_from_slice(&[a, b, a + 1]);
+            }
+            if ring != SPHERE_RINGS - 1 {
+                mesh.indices.extend_from_slice(&[a + 1, b, b + 1]);
+            }
+        }
+    }
+}
+
+// Nombre válido en OBJ/MTL ('#' abre comentario)
+fn material_name(scene: &Scene, id: MaterialId) -> String {
+    let name = scene.materials.name(id).map_or_else(|| format!("material_{}", id), str::to_string);
+    name.chars().map(|c| if c.is_whitespace() || c == '#' { '_' } else { c }).collect()
+}
+
+// Nombres de los materiales exportados, sin repetidos: al limpiar "hoja roja" y "hoja#roja"
+// ambos quedan "hoja_roja", así que el segundo lleva además su índice
+fn material_names(scene: &Scene, ids: impl Iterator<Item = MaterialId>) -> HashMap<MaterialId, String> {
+    let mut names = HashMap::new();
+    let mut used = HashSet::new();
+    for id in ids {
+        let mut name = material_name(scene, id);
+        while used.contains(&name) {
+            name = format!("{}_{}", name, id);
+        }
+        used.insert(name.clone());
+        names.insert(id, name);
+    }
+    names
+}
+
+// Ruta de la textura `id` relativa a la carpeta del archivo exportado (con '/')
+fn texture_path(scene: &Scene, id: Option<usize>, export_dir: &Path) -> Option<String> {
+    let path = scene.textures.path(id?)?;
+    let target = std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+    let relative = std::fs::canonicalize(export_dir)
+        .ok()
+        .and_then(|dir| relative_path(&dir, &target))
+        .unwrap_or(target);
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
+
+fn relative_path(from_dir: &Path, to: &Path) -> Option<PathBuf> {
+    let from: Vec<Component> = from_dir.components().collect();
+    let to_components: Vec<Component> = to.components().collect();
+    if from.first() != to_components.first() {
+        return None; // otra unidad o ruta relativa
+    }
+    let common = from.iter().zip(&to_components).take_while(|(a, b)| a == b).count();
+    let mut relative = PathBuf::new();
+    for _ in common..from.len() {
+        relative.push("..");
+    }
+    for component in &to_components[common..] {
+        relative.push(component);
+    }
+    Some(relative)
+}
+
+fn export_dir(file_path: &str) -> &Path {
+    match Path::new(file_path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    }
+}
+
+// Escribe `file_path` (.obj) y a su lado el .mtl con el mismo nombre
+pub fn export_obj(scene: &Scene, file_path: &str, options: &ExportOptions) -> Result<(), String> {
+    let meshes = build_meshes(scene, options);
+    let names = material_names(scene, meshes.keys().copied());
+    let dir = export_dir(file_path);
+    let mtl_path = Path::new(file_path).with_extension("mtl");
+    let mtl_name = mtl_path.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+
+    let mut obj = String::new();
+    let _ = writeln!(obj, "# Exportado del raytracer\nmtllib {}", mtl_name);
+    let mut base = 1; // los índices de OBJ empiezan en 1 y son globales
+    for (id, mesh) in &meshes {
+        if mesh.indices.is_empty() {
+            continue;
+        }
+        let name = &names[id];
+        let _ = writeln!(obj, "o {}\nusemtl {}", name, name);
+        for p in &mesh.positions {
+            let _ = writeln!(obj, "v {} {} {}", p.x, p.y, p.z);
+        }
+        for uv in &mesh.uvs {
+            let _ = writeln!(obj, "vt {} {}", uv.x, uv.y);
+        }
+        for n in &mesh.normals {
+            let _ = writeln!(obj, "vn {} {} {}", n.x, n.y, n.z);
+        }
+        for triangle in mesh.indices.chunks(3) {
+            let [a, b, c] = [triangle[0] + base, triangle[1] + base, triangle[2] + base];
+            let _ = writeln!(obj, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}");
+        }
+        base += mesh.positions.len() as u32;
+    }
+
+    let mut mtl = String::from("# Exportado del raytracer\n");
+    for id in meshes.keys() {
+        let material = &scene.materials.materials()[*id as usize];
+        let d = material.diffuse;
+        let _ = writeln!(mtl, "\nnewmtl {}", names[id]);
+        let _ = writeln!(mtl, "Kd {} {} {}", d.x, d.y, d.z);
+        let ks = if matches!(material.model, ShadingModel::Pbr) { 0.04 } else { material.albedo[1] };
+        let _ = writeln!(mtl, "Ks {} {} {}", ks, ks, ks);
+        let _ = writeln!(mtl, "Ns {}", material.specular);
+        let _ = writeln!(mtl, "Pr {}\nPm {}", roughness(material), material.metallic);
+        let _ = writeln!(mtl, "d {}\nNi {}\nillum 2", 1.0 - material.transparency, material.ior);
+        if material.is_emissive() {
+            let e = material.emission * material.emission_strength;
+            let _ = writeln!(mtl, "Ke {} {} {}", e.x, e.y, e.z);
+        }
+        if let Some(path) = texture_path(scene, material.texture_id, dir) {
+            let _ = writeln!(mtl, "map_Kd {}", path);
+        }
+        if let Some(path) = texture_path(scene, material.normal_map_id, dir) {
+            let _ = writeln!(mtl, "map_Bump -bm {} {}", material.normal_strength, path);
+        }
+        if let Some(path) = texture_path(scene, material.emission_texture_id, dir) {
+            let _ = writeln!(mtl, "map_Ke {}", path);
+        }
+    }
+
+    std::fs::write(file_path, obj).map_err(|e| format!("{}: {}", file_path, e))?;
+    std::fs::write(&mtl_path, mtl).map_err(|e| format!("{}: {}", mtl_path.display(), e))
+}
+
+// Rugosidad para los visores PBR; en Phong se aproxima con el exponente especular
+fn roughness(material: &Material) -> f32 {
+    match material.model {
+        ShadingModel::Pbr => material.roughness,
+        ShadingModel::Phong => (2.0 / (material.specular + 2.0)).sqrt().max(material.roughness),
+    }
+}
+
+pub fn export_glb(scene: &Scene, file_path: &str, options: &ExportOptions) -> Result<(), String> {
+    let glb = glb_bytes(scene, export_dir(file_path), options);
+    std::fs::write(file_path, glb).map_err(|e| format!("{}: {}", file_path, e))
+}
+
+// Contenido del .glb; `dir` es la carpeta de destino, para las rutas de las texturas
+fn glb_bytes(scene: &Scene, dir: &Path, options: &ExportOptions) -> Vec<u8> {
+    let meshes = build_meshes(scene, options);
+    let names = material_names(scene, meshes.keys().copied());
+
+    let mut bin: Vec<u8> = Vec::new();
+    let mut buffer_views = Vec::new();
+    let mut accessors = Vec::new();
+    let mut primitives = Vec::new();
+    let mut materials = Vec::new();
+    let mut images = Vec::new();
+    let mut textures = Vec::new();
+    let mut texture_indices: HashMap<usize, usize> = HashMap::new();
+    let mut uses_emissive_strength = false;
+
+    // vista + accesor sobre los bytes agregados al final del buffer
+    let mut push_accessor = |bin: &mut Vec<u8>, bytes: Vec<u8>, target: u32, accessor: Value| {
+        buffer_views.push(json!({
+            "buffer": 0,
+            "byteOffset": bin.len(),
+            "byteLength": bytes.len(),
+            "target": target,
+        }));
+        bin.extend_from_slice(&bytes);
+        let mut accessor = accessor;
+        accessor["bufferView"] = json!(buffer_views.len() - 1);
+        accessors.push(accessor);
+        accessors.len() - 1
+    };
+
+    for (id, mesh) in &meshes {
+        if mesh.indices.is_empty() {
+            continue;
+        }
+        let count = mesh.positions.len();
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for p in &mesh.positions {
+            for (axis, v) in [p.x, p.y, p.z].into_iter().enumerate() {
+                min[axis] = min[axis].min(v);
+                max[axis] = max[axis].max(v);
+            }
+        }
+
+        const ARRAY_BUFFER: u32 = 34962;
+        const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+        const FLOAT: u32 = 5126;
+        const UNSIGNED_INT: u32 = 5125;
+        let floats = |values: &mut dyn Iterator<Item = f32>| values.flat_map(f32::to_le_bytes).collect::<Vec<u8>>();
+
+        let position = push_accessor(
+            &mut bin,
+            floats(&mut mesh.positions.iter().flat_map(|p| [p.x, p.y, p.z])),
+            ARRAY_BUFFER,
+            json!({ "componentType": FLOAT, "count": count, "type": "VEC3", "min": min, "max": max }),
+        );
+        let normal = push_accessor(
+            &mut bin,
+            floats(&mut mesh.normals.iter().flat_map(|n| [n.x, n.y, n.z])),
+            ARRAY_BUFFER,
+            json!({ "componentType": FLOAT, "count": count, "type": "VEC3" }),
+        );
+        // glTF cuenta v desde arriba
+        let texcoord = push_accessor(
+            &mut bin,
+            floats(&mut mesh.uvs.iter().flat_map(|uv| [uv.x, 1.0 - uv.y])),
+            ARRAY_BUFFER,
+            json!({ "componentType": FLOAT, "count": count, "type": "VEC2" }),
+        );
+        let indices = push_accessor(
+            &mut bin,
+            mesh.indices.iter().flat_map(|i| i.to_le_bytes()).collect(),
+            ELEMENT_ARRAY_BUFFER,
+            json!({ "componentType": UNSIGNED_INT, "count": mesh.indices.len(), "type": "SCALAR" }),
+        );
+
+        let material = &scene.materials.materials()[*id as usize];
+        let mut texture = |texture_id: Option<usize>| -> Option<usize> {
+            let texture_id = texture_id?;
+            if let Some(index) = texture_indices.get(&texture_id) {
+                return Some(*index);
+            }
+            let uri = texture_path(scene, Some(texture_id), dir)?.replace(' ', "%20");
+            images.push(json!({ "uri": uri }));
+            textures.push(json!({ "source": images.len() - 1, "sampler": 0 }));
+            texture_indices.insert(texture_id, textures.len() - 1);
+            Some(textures.len() - 1)
+        };
+
+        let d = material.diffuse;
+        let mut pbr = json!({
+            "baseColorFactor": [d.x, d.y, d.z, 1.0 - material.transparency],
+            "metallicFactor": material.metallic,
+            "roughnessFactor": roughness(material),
+        });
+        if let Some(index) = texture(material.texture_id) {
+            pbr["baseColorTexture"] = json!({ "index": index });
+        }
+        let mut gltf_material = json!({
+            "name": names[id],
+            "pbrMetallicRoughness": pbr,
+        });
+        if let Some(index) = texture(material.normal_map_id) {
+            gltf_material["normalTexture"] = json!({ "index": index, "scale": material.normal_strength });
+        }
+        if material.is_emissive() {
+            let e = material.emission;
+            gltf_material["emissiveFactor"] = json!([e.x, e.y, e.z]);
+            if material.emission_strength != 1.0 {
+                uses_emissive_strength = true;
+                gltf_material["extensions"] =
+                    json!({ "KHR_materials_emissive_strength": { "emissiveStrength": material.emission_strength } });
+            }
+        }
+        if let Some(index) = texture(material.emission_texture_id) {
+            gltf_material["emissiveTexture"] = json!({ "index": index });
+        }
+        if material.alpha_cutoff > 0.0 {
+            gltf_material["alphaMode"] = json!("MASK");
+            gltf_material["alphaCutoff"] = json!(material.alpha_cutoff);
+        } else if material.transparency > 0.0 {
+            gltf_material["alphaMode"] = json!("BLEND");
+        }
+        if material.transparency > 0.0 || material.translucency > 0.0 {
+            gltf_material["doubleSided"] = json!(true);
+        }
+        materials.push(gltf_material);
+
+        primitives.push(json!({
+            "attributes": { "POSITION": position, "NORMAL": normal, "TEXCOORD_0": texcoord },
+            "indices": indices,
+            "material": materials.len() - 1,
+        }));
+    }
+
+    let mut document = json!({
+        "asset": { "version": "2.0", "generator": "Proyecto2_Raytracer" },
+        "scene": 0,
+        "scenes": [{ "nodes": [] }],
+    });
+    // glTF no admite mallas sin primitivos ni buffers vacíos
+    if !primitives.is_empty() {
+        document["scenes"] = json!([{ "nodes": [0] }]);
+        document["nodes"] = json!([{ "mesh": 0, "name": "scene" }]);
+        document["meshes"] = json!([{ "primitives": primitives }]);
+        document["materials"] = json!(materials);
+        document["accessors"] = json!(accessors);
+        document["bufferViews"] = json!(buffer_views);
+        document["buffers"] = json!([{ "byteLength": bin.len() }]);
+    }
+    if !images.is_empty() {
+        document["images"] = json!(images);
+        document["textures"] = json!(textures);
+        document["samplers"] = json!([{ "wrapS": 10497, "wrapT": 10497 }]); // REPEAT, como el render
+    }
+    if uses_emissive_strength {
+        document["extensionsUsed"] = json!(["KHR_materials_emissive_strength"]);
+    }
+
+    let mut json_chunk = document.to_string().into_bytes();
+    while !json_chunk.len().is_multiple_of(4) {
+        json_chunk.push(b' ');
+    }
+    while !bin.len().is_multiple_of(4) {
+        bin.push(0);
+    }
+
+    let total = 12 + 8 + json_chunk.len() + 8 + bin.len();
+    let mut glb = Vec::with_capacity(total);
+    glb.extend_from_slice(b"glTF");
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&(total as u32).to_le_bytes());
+    glb.extend_from_slice(&(json_chunk.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"JSON");
+    glb.extend_from_slice(&json_chunk);
+    glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"BIN\0");
+    glb.extend_from_slice(&bin);
+    glb
+}
+
+// Elige el formato por la extensión: .obj (con .mtl) o .glb
+pub fn export_scene(scene: &Scene, file_path: &str, options: &ExportOptions) -> Result<(), String> {
+    let extension = Path::new(file_path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "obj" => export_obj(scene, file_path, options),
+        "glb" => export_glb(scene, file_path, options),
+        _ => Err(format!("{}: unknown export format (use .obj or .glb)", file_path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::Camera;
+    use crate::material_library::MaterialLibrary;
+    use crate::math::Color;
+    use crate::texture::TextureManager;
+
+    fn scene(objects: Vec<Primitive>, names: &[&str]) -> Scene {
+        let mut materials = MaterialLibrary::new();
+        for name in names {
+            materials.insert(name, Material::new(Vector3::one(), [1.0, 0.0], 1.0)).unwrap();
+        }
+        Scene {
+            objects,
+            materials,
+            lights: Vec::new(),
+            textures: TextureManager::new(),
+            camera: Camera::new(Vector3::new(0.0, 0.0, 5.0), Vector3::zero(), Vector3::new(0.0, 1.0, 0.0)),
+            background: Color::BLACK,
+        }
+    }
+
+    fn cube(x: f32, material_id: MaterialId) -> Primitive {
+        Primitive::Cube(Cube { center: Vector3::new(x, 0.0, 0.0), size: 1.0, material_id })
+    }
+
+    #[test]
+    fn culling_drops_the_faces_between_neighbours() {
+        let scene = scene(vec![cube(0.0, 0), cube(1.0, 0)], &["stone"]);
+        let triangles = |cull_hidden_faces| build_meshes(&scene, &ExportOptions { cull_hidden_faces })[&0].indices.len() / 3;
+        assert_eq!(triangles(false), 2 * 6 * 2);
+        assert_eq!(triangles(true), 2 * 5 * 2);
+    }
+
+    #[test]
+    fn triangles_wind_counter_clockwise_seen_from_outside() {
+        let sphere = Primitive::Sphere(Sphere { center: Vector3::new(3.0, 0.0, 0.0), radius: 1.0, material_id: 1 });
+        let scene = scene(vec![cube(0.0, 0), sphere], &["stone", "ball"]);
+        for mesh in build_meshes(&scene, &ExportOptions::default()).values() {
+            for triangle in mesh.indices.chunks(3) {
+                let [a, b, c] = [0, 1, 2].map(|i| mesh.positions[triangle[i] as usize]);
+                let normal = mesh.normals[triangle[0] as usize] + mesh.normals[triangle[1] as usize] + mesh.normals[triangle[2] as usize];
+                assert!((b - a).cross(c - a).dot(normal) > 0.0, "{:?}", triangle);
+            }
+        }
+    }
+
+    #[test]
+    fn glb_chunks_are_aligned_and_views_fit_the_buffer() {
+        let scene = scene(vec![cube(0.0, 0), cube(1.0, 1)], &["stone", "wood"]);
+        let glb = glb_bytes(&scene, Path::new("."), &ExportOptions::default());
+        let u32_at = |offset: usize| u32::from_le_bytes(glb[offset..offset + 4].try_into().unwrap()) as usize;
+
+        assert_eq!(&glb[0..4], b"glTF");
+        assert_eq!(u32_at(4), 2);
+        assert_eq!(u32_at(8), glb.len());
+
+        let json_length = u32_at(12);
+        assert_eq!(&glb[16..20], b"JSON");
+        assert_eq!(json_length % 4, 0);
+        let document: Value = serde_json::from_slice(&glb[20..20 + json_length]).unwrap();
+
+        let bin_header = 20 + json_length;
+        let bin_length = u32_at(bin_header);
+        assert_eq!(&glb[bin_header + 4..bin_header + 8], b"BIN\0");
+        assert_eq!(bin_length % 4, 0);
+        assert_eq!(bin_header + 8 + bin_length, glb.len());
+
+        // el buffer declarado cabe en el chunk, que solo agrega el relleno hasta múltiplo de 4
+        let byte_length = document["buffers"][0]["byteLength"].as_u64().unwrap() as usize;
+        assert!(byte_length <= bin_length && bin_length - byte_length < 4);
+        for view in document["bufferViews"].as_array().unwrap() {
+            let offset = view["byteOffset"].as_u64().unwrap() as usize;
+            let length = view["byteLength"].as_u64().unwrap() as usize;
+            assert!(offset + length <= byte_length, "{}", view);
+        }
+    }
+
+    #[test]
+    fn cleaned_material_names_stay_unique() {
+        let scene = scene(vec![cube(0.0, 0), cube(2.0, 1), cube(4.0, 2)], &["hoja roja", "hoja#roja", "hoja_roja"]);
+        let names = material_names(&scene, 0..3);
+        let unique: HashSet<&String> = names.values().collect();
+        assert_eq!(unique.len(), 3, "{:?}", names);
+        assert!(names.values().all(|name| !name.contains(' ') && !name.contains('#')));
+    }
+}
@@ -1,4 +1,6 @@
-use raylib::prelude::*;
+use crate::math::Color;
+#[cfg(feature = "window")]
+use raylib::prelude::{RaylibDraw, RaylibHandle, RaylibThread};
 
 pub struct Framebuffer {
     pub width: u32,
@@ -11,7 +13,10 @@ pub struct Framebuffer {
 
 impl Framebuffer {
     pub fn new(width: u32, height: u32, background_color: Color) -> Self {
-        let buffer_size = (width * height) as usize;
+        // en usize: en u32 el producto se desborda antes de llegar a la memoria que ocupa
+        let buffer_size = (width as usize)
+            .checked_mul(height as usize)
+            .expect("framebuffer size overflows usize");
         let color_buffer = vec![background_color; buffer_size];
         
         Framebuffer {
@@ -31,7 +36,7 @@ impl Framebuffer {
     
     pub fn set_pixel(&mut self, x: u32, y: u32) {
         if x < self.width && y < self.height {
-            let index = (y * self.width + x) as usize;
+            let index = y as usize * self.width as usize + x as usize;
             if index < self.color_buffer.len() {
                 self.color_buffer[index] = self.current_color;
             }
@@ -45,7 +50,23 @@ impl Framebuffer {
     pub fn set_current_color(&mut self, color: Color) {
         self.current_color = color;
     }
+
+    // Guarda el color buffer como PNG (no necesita ventana)
+    pub fn save_png(&self, file_path: &str) -> Result<(), String> {
+        let mut pixels = Vec::with_capacity(self.color_buffer.len() * 4);
+        for color in &self.color_buffer {
+            pixels.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+
+        let image = image::RgbaImage::from_raw(self.width, self.height, pixels)
+            .ok_or_else(|| "framebuffer size does not match its buffer".to_string())?;
+        image
+            .save_with_format(file_path, image::ImageFormat::Png)
+            .map_err(|e| format!("{}: {}", file_path, e))
+    }
     
+    // Solo con la ventana de raylib (feature `window`)
+    #[cfg(feature = "window")]
     pub fn swap_buffers(&mut self, window: &mut RaylibHandle, raylib_thread: &RaylibThread) {
         let screen_width = window.get_screen_width() as f32;
         let screen_height = window.get_screen_height() as f32;
@@ -61,7 +82,7 @@ impl Framebuffer {
         let offset_y = (screen_height - scaled_height) / 2.0;
 
         let mut rendering = window.begin_drawing(raylib_thread);
-        rendering.clear_background(self.background_color);
+        rendering.clear_background(to_raylib(self.background_color));
 
         // Dibujar solo píxeles que han cambiado
         // o usar rectángulos en lugar de píxeles individuales
@@ -72,7 +93,7 @@ impl Framebuffer {
             
             for y in 0..self.height {
                 for x in 0..self.width {
-                    let index = (y * self.width + x) as usize;
+                    let index = y as usize * self.width as usize + x as usize;
                     if index < self.color_buffer.len() {
                         let color = self.color_buffer[index];
                         if color != self.background_color { // Solo dibujar píxeles no vacíos
@@ -84,7 +105,7 @@ impl Framebuffer {
                                 screen_y as i32,
                                 pixel_size,
                                 pixel_size,
-                                color
+                                to_raylib(color)
                             );
                         }
                     }
@@ -94,14 +115,14 @@ impl Framebuffer {
             // Si la escala es pequeña, usar píxeles
             for y in 0..self.height {
                 for x in 0..self.width {
-                    let index = (y * self.width + x) as usize;
+                    let index = y as usize * self.width as usize + x as usize;
                     if index < self.color_buffer.len() {
                         let color = self.color_buffer[index];
                         if color != self.background_color {
                             let screen_x = offset_x + (x as f32 * scale);
                             let screen_y = offset_y + (y as f32 * scale);
                             
-                            rendering.draw_pixel(screen_x as i32, screen_y as i32, color);
+                            rendering.draw_pixel(screen_x as i32, screen_y as i32, to_raylib(color));
                         }
                     }
                 }
@@ -109,3 +130,8 @@ impl Framebuffer {
         }
     }
 }
+
+#[cfg(feature = "window")]
+fn to_raylib(color: Color) -> raylib::prelude::Color {
+    raylib::prelude::Color::new(color.r, color.g, color.b, color.a)
+}
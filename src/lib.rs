@@ -0,0 +1,28 @@
+// lib.rs
+// Núcleo del raytracer: escena, materiales, texturas y render. El binario solo
+// se encarga de la línea de comandos y de la ventana
+pub mod camera;
+pub mod cube;
+pub mod export;
+pub mod framebuffer;
+pub mod light;
+pub mod material;
+pub mod material_library;
+pub mod math;
+pub mod pbr;
+pub mod prefab;
+pub mod primitive;
+pub mod procedural;
+pub mod ray_intersect;
+pub mod render;
+pub mod sampling;
+pub mod scene;
+pub mod scene_file;
+pub mod sphere;
+pub mod terrain;
+pub mod texture;
+pub mod vox;
+pub mod voxel_file;
+pub mod voxel_grid;
+pub mod voxel_map;
+pub mod water;
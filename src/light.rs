@@ -1,9 +1,12 @@
-use raylib::prelude::*;
+use crate::math::Vector3;
+use crate::material::Material;
 
 pub struct Light {
     pub position: Vector3,
     pub color: Vector3,
     pub intensity: f32,
+    pub attenuation: f32,            // caída con la distancia: I / (1 + att * d²); 0 = sin caída
+    pub source: Option<usize>,       // índice del objeto emisivo que la contiene (no le hace sombra)
 }
 
 impl Light {
@@ -12,6 +15,24 @@ impl Light {
             position,
             color,
             intensity,
+            attenuation: 0.0,
+            source: None,
         }
     }
+
+    // Luz puntual en el centro de un objeto emisivo (farol, glowstone, lava)
+    pub fn from_emissive(position: Vector3, material: &Material, source: usize) -> Self {
+        Light {
+            position,
+            color: material.emission,
+            intensity: material.emission_strength,
+            attenuation: 0.5,
+            source: Some(source),
+        }
+    }
+
+    pub fn intensity_at(&self, point: Vector3) -> f32 {
+        let distance2 = (self.position - point).length_sqr();
+        self.intensity / (1.0 + self.attenuation * distance2)
+    }
 }
\ No newline at end of file
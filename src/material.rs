@@ -1,15 +1,123 @@
 // material.rs
-use raylib::prelude::{Color, Vector3};
+use crate::math::{Color, Vector2, Vector3};
+use crate::water::WaterWaves;
+
+// Índice en la tabla de materiales compartida (los primitivos no copian el Material)
+pub type MaterialId = u16;
+
+// Cómo se combina el texel con `diffuse` cuando el material tiene textura
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureBlend {
+    Replace,                         // el texel reemplaza al difuso
+    Multiply,                        // texel * difuso (tinte)
+    Overlay,                         // overlay: oscurece/aclara conservando el detalle
+    Lerp(f32),                       // 0 = texel .. 1 = difuso
+}
+
+impl TextureBlend {
+    pub fn apply(&self, texel: Vector3, diffuse: Vector3) -> Vector3 {
+        match *self {
+            TextureBlend::Replace => texel,
+            TextureBlend::Multiply => texel * diffuse,
+            TextureBlend::Overlay => Vector3::new(
+                overlay(texel.x, diffuse.x),
+                overlay(texel.y, diffuse.y),
+                overlay(texel.z, diffuse.z),
+            ),
+            TextureBlend::Lerp(t) => texel * (1.0 - t) + diffuse * t,
+        }
+    }
+}
+
+fn overlay(base: f32, blend: f32) -> f32 {
+    if base < 0.5 {
+        2.0 * base * blend
+    } else {
+        1.0 - 2.0 * (1.0 - base) * (1.0 - blend)
+    }
+}
+
+// Transformación de UV animada con el reloj global:
+// uv' = rotar(uv - 0.5) * scale + 0.5 + offset, con offset y rotación avanzando según su velocidad
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvTransform {
+    pub offset: Vector2,
+    pub scale: Vector2,
+    pub rotation: f32,               // radianes
+    pub offset_velocity: Vector2,    // UV por segundo
+    pub rotation_velocity: f32,      // radianes por segundo
+}
+
+impl UvTransform {
+    pub fn identity() -> Self {
+        UvTransform {
+            offset: Vector2::zero(),
+            scale: Vector2::one(),
+            rotation: 0.0,
+            offset_velocity: Vector2::zero(),
+            rotation_velocity: 0.0,
+        }
+    }
+
+    pub fn scrolling(offset_velocity: Vector2) -> Self {
+        UvTransform { offset_velocity, ..UvTransform::identity() }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == UvTransform::identity()
+    }
+
+    // Ángulo total en el instante `time`
+    pub fn angle(&self, time: f32) -> f32 {
+        self.rotation + self.rotation_velocity * time
+    }
+
+    pub fn apply(&self, uv: Vector2, time: f32) -> Vector2 {
+        let angle = self.angle(time);
+        let (sin, cos) = angle.sin_cos();
+        let centered = uv - Vector2::new(0.5, 0.5);
+        let rotated = Vector2::new(
+            centered.x * cos - centered.y * sin,
+            centered.x * sin + centered.y * cos,
+        );
+        rotated * self.scale + Vector2::new(0.5, 0.5) + self.offset + self.offset_velocity * time
+    }
+}
+
+// Modelo de iluminación directa del material
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShadingModel {
+    Phong,                           // albedo[0] difuso, albedo[1] especular, `specular` exponente
+    Pbr,                             // color base + metallic/roughness (GGX, Smith, Schlick)
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct Material {
     pub diffuse: Vector3,
     pub albedo: [f32; 2],
     pub specular: f32,
+    pub model: ShadingModel,
+    pub metallic: f32,               // solo Pbr: 0 dieléctrico .. 1 metal
+    pub roughness: f32,              // 0 pulido .. 1 mate (también abre reflejos/refracción)
+    pub gloss_samples: u32,          // rayos por reflejo/refracción cuando roughness > 0
     pub texture_id: Option<usize>,
+    pub texture_blend: TextureBlend,
+    pub uv_transform: UvTransform,
+    pub normal_map_id: Option<usize>,
+    pub normal_strength: f32,        // escala de la inclinación del normal map (1 = tal cual)
+    pub height_map_id: Option<usize>,
+    pub parallax_scale: f32,         // profundidad máxima del relieve, en unidades de UV
+    pub parallax_steps: u32,         // capas que recorre el parallax occlusion mapping
     pub transparency: f32,           // 0 opaco .. 1 totalmente transparente
     pub reflectivity: f32,           // 0 mate .. 1 espejo
     pub ior: f32,                    // índice de refracción (agua 1.33, vidrio 1.5)
+    pub secondary_rays: bool,        // fuerza rayos de reflejo/refracción (ver traces_secondary_rays)
+    pub waves: Option<WaterWaves>,   // modelo de agua: olas animadas en la normal
+    pub translucency: f32,           // luz que atraviesa caras delgadas desde atrás (hojas)
+    pub alpha_cutoff: f32,           // texels con alfa menor se descartan (0 = sin recorte)
+    pub emission: Vector3,           // color emitido (se suma al sombreado)
+    pub emission_strength: f32,      // 0 = no emite
+    pub emission_texture_id: Option<usize>, // modula `emission` (p. ej. vetas de lava)
 }
 
 impl Material {
@@ -18,18 +126,83 @@ impl Material {
             diffuse,
             albedo,
             specular,
+            model: ShadingModel::Phong,
+            metallic: 0.0,
+            roughness: 0.0,
+            gloss_samples: 1,
             texture_id: None,
+            texture_blend: TextureBlend::Replace,
+            uv_transform: UvTransform::identity(),
+            normal_map_id: None,
+            normal_strength: 1.0,
+            height_map_id: None,
+            parallax_scale: 0.0,
+            parallax_steps: 0,
             transparency: 0.0,
             reflectivity: 0.0,
             ior: 1.0,
+            secondary_rays: false,
+            waves: None,
+            translucency: 0.0,
+            alpha_cutoff: 0.0,
+            emission: Vector3::zero(),
+            emission_strength: 0.0,
+            emission_texture_id: None,
         }
     }
 
+    // Material físico: `diffuse` hace de color base; dieléctrico semimate por defecto
+    pub fn pbr(base_color: Vector3, metallic: f32, roughness: f32) -> Self {
+        Material {
+            model: ShadingModel::Pbr,
+            metallic: metallic.clamp(0.0, 1.0),
+            roughness: roughness.clamp(0.0, 1.0),
+            gloss_samples: 8,
+            ..Material::new(base_color, [1.0, 1.0], 0.0)
+        }
+    }
+
+    pub fn pbr_default(base_color: Vector3) -> Self {
+        Material::pbr(base_color, 0.0, 0.5)
+    }
+
+    // Reflejos/refracción borrosos: promedia `samples` rayos en un lóbulo de ancho `roughness`
+    pub fn with_gloss(mut self, roughness: f32, samples: u32) -> Self {
+        self.roughness = roughness.clamp(0.0, 1.0);
+        self.gloss_samples = samples.max(1);
+        self
+    }
+
      pub fn with_texture(mut self, tex_id: usize) -> Self {
         self.texture_id = Some(tex_id);
         self
     }
 
+    pub fn with_texture_blend(mut self, blend: TextureBlend) -> Self {
+        self.texture_blend = blend;
+        self
+    }
+
+    pub fn with_uv_transform(mut self, transform: UvTransform) -> Self {
+        self.uv_transform = transform;
+        self
+    }
+
+    // Normal map en espacio tangente (convención OpenGL: verde = +v)
+    pub fn with_normal_map(mut self, tex_id: usize, strength: f32) -> Self {
+        self.normal_map_id = Some(tex_id);
+        self.normal_strength = strength;
+        self
+    }
+
+    // Height map para parallax occlusion mapping (blanco = superficie, negro = hundido)
+    pub fn with_parallax(mut self, tex_id: usize, scale: f32, steps: u32) -> Self {
+        self.height_map_id = Some(tex_id);
+        self.parallax_scale = scale;
+        self.parallax_steps = steps.max(1);
+        self
+    }
+
     pub fn with_optics(mut self, transparency: f32, reflectivity: f32, ior: f32) -> Self {
         self.transparency = transparency;
         self.reflectivity = reflectivity;
@@ -37,15 +210,77 @@ impl Material {
         self
     }
 
+    pub fn with_secondary_rays(mut self) -> Self {
+        self.secondary_rays = true;
+        self
+    }
+
+    // Reflejo y refracción con rayos son opt-in: el agua, los materiales con rugosidad
+    // (lóbulo de reflejo borroso), los Pbr (reflejo con Fresnel) o `secondary_rays`.
+    // El resto usa reflectivity/transparency solo al exportar
+    pub fn traces_secondary_rays(&self) -> bool {
+        self.secondary_rays || self.waves.is_some() || self.roughness > 0.0 || self.model == ShadingModel::Pbr
+    }
+
+    pub fn with_waves(mut self, waves: WaterWaves) -> Self {
+        self.waves = Some(waves);
+        self
+    }
+
+    pub fn with_translucency(mut self, translucency: f32) -> Self {
+        self.translucency = translucency.clamp(0.0, 1.0);
+        self
+    }
+
+    // Usa el canal alfa de la textura como máscara: los rayos atraviesan los texels
+    // con alfa por debajo de `threshold` (hojas, rejas, etc.)
+    pub fn with_alpha_cutout(mut self, threshold: f32) -> Self {
+        self.alpha_cutoff = threshold;
+        self
+    }
+
+    pub fn with_emission(mut self, color: Vector3, strength: f32) -> Self {
+        self.emission = color;
+        self.emission_strength = strength;
+        self
+    }
+
+    pub fn with_emission_texture(mut self, tex_id: usize) -> Self {
+        self.emission_texture_id = Some(tex_id);
+        self
+    }
+
+    pub fn is_emissive(&self) -> bool {
+        self.emission_strength > 0.0
+    }
+
     pub fn black() -> Self {
         Material {
             diffuse: Vector3::zero(),
             albedo: [0.0, 0.0],
             specular: 0.0,
+            model: ShadingModel::Phong,
+            metallic: 0.0,
+            roughness: 0.0,
+            gloss_samples: 1,
             texture_id: None,
+            texture_blend: TextureBlend::Replace,
+            uv_transform: UvTransform::identity(),
+            normal_map_id: None,
+            normal_strength: 1.0,
+            height_map_id: None,
+            parallax_scale: 0.0,
+            parallax_steps: 0,
             transparency: 0.0,
             reflectivity: 0.0,
             ior: 1.0,
+            secondary_rays: false,
+            waves: None,
+            translucency: 0.0,
+            alpha_cutoff: 0.0,
+            emission: Vector3::zero(),
+            emission_strength: 0.0,
+            emission_texture_id: None,
         }
     }
 }
@@ -0,0 +1,516 @@
+// material_library.rs
+// Biblioteca de materiales con nombre, cargada desde un archivo TOML:
+//
+//     [wood]
+//     diffuse = [0.55, 0.38, 0.25]
+//     albedo = [0.95, 0.05]
+//     specular = 12.0
+//     texture = "wood.png"          # relativo al archivo de la biblioteca
+//     reflectivity = 0.05
+//
+//     [grass_dark]
+//     extends = "grass"             # copia otro material y cambia lo indicado (también
+//                                   # dentro de uv_transform)
+//     diffuse = [0.16, 0.35, 0.16]
+//     texture_blend = "overlay"
+//
+//     [wood_rings]
+//     extends = "wood"
+//     texture = { pattern = "wood", rings = 6.0 }   # textura procedural en vez de imagen
+use crate::math::{Vector2, Vector3};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::material::{Material, MaterialId, ShadingModel, TextureBlend};
+use crate::procedural::ProceduralTexture;
+use crate::texture::{TextureDef, TextureManager};
+use crate::water::WaterWaves;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MaterialDef {
+    pub extends: Option<String>,
+    pub model: Option<String>,
+    pub diffuse: Option<[f32; 3]>,
+    pub albedo: Option<[f32; 2]>,
+    pub specular: Option<f32>,
+    pub metallic: Option<f32>,
+    pub roughness: Option<f32>,
+    pub gloss_samples: Option<u32>,
+    pub texture: Option<TextureDef>,
+    pub texture_blend: Option<TextureBlendDef>,
+    pub uv_transform: Option<UvTransformDef>,
+    pub normal_map: Option<TextureDef>,
+    pub normal_strength: Option<f32>,
+    pub height_map: Option<TextureDef>,
+    pub parallax_scale: Option<f32>,
+    pub parallax_steps: Option<u32>,
+    pub transparency: Option<f32>,
+    pub reflectivity: Option<f32>,
+    pub ior: Option<f32>,
+    pub secondary_rays: Option<bool>,
+    pub waves: Option<WavesDef>,
+    pub translucency: Option<f32>,
+    pub alpha_cutoff: Option<f32>,
+    pub emission: Option<[f32; 3]>,
+    pub emission_strength: Option<f32>,
+    pub emission_texture: Option<TextureDef>,
+}
+
+// "replace" | "multiply" | "overlay" | { lerp = 0.5 }
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum TextureBlendDef {
+    Name(String),
+    Lerp { lerp: f32 },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UvTransformDef {
+    pub offset: Option<[f32; 2]>,
+    pub scale: Option<[f32; 2]>,
+    pub rotation: Option<f32>,
+    pub offset_velocity: Option<[f32; 2]>,
+    pub rotation_velocity: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WavesDef {
+    pub amplitude: f32,
+    pub wavelength: f32,
+    pub speed: Option<f32>,
+    pub direction: Option<f32>,
+}
+
+// También es la tabla de materiales del render: los primitivos guardan el MaterialId
+pub struct MaterialLibrary {
+    materials: Vec<Material>,
+    ids: HashMap<String, MaterialId>,
+}
+
+impl Default for MaterialLibrary {
+    fn default() -> Self {
+        MaterialLibrary::new()
+    }
+}
+
+impl MaterialLibrary {
+    pub fn new() -> Self {
+        MaterialLibrary {
+            materials: Vec::new(),
+            ids: HashMap::new(),
+        }
+    }
+
+    pub fn load(file_path: &str, texture_manager: &mut TextureManager) -> Result<Self, String> {
+        let source = std::fs::read_to_string(file_path)
+            .map_err(|e| format!("{}: {}", file_path, e))?;
+        let base_dir = Path::new(file_path).parent().unwrap_or(Path::new(""));
+        Self::from_toml_str(&source, base_dir, texture_manager)
+            .map_err(|e| format!("{}: {}", file_path, e))
+    }
+
+    // `base_dir` es la carpeta contra la que se resuelven las rutas de texturas
+    pub fn from_toml_str(source: &str, base_dir: &Path, texture_manager: &mut TextureManager) -> Result<Self, String> {
+        let defs: HashMap<String, MaterialDef> = toml::from_str(source).map_err(|e| e.to_string())?;
+        Self::from_defs(&defs, base_dir, texture_manager)
+    }
+
+    pub fn from_defs(
+        defs: &HashMap<String, MaterialDef>,
+        base_dir: &Path,
+        texture_manager: &mut TextureManager,
+    ) -> Result<Self, String> {
+        Self::from_defs_with_textures(defs, base_dir, texture_manager, &HashMap::new())
+    }
+
+    // Igual que `from_defs`, pero los campos de textura pueden nombrar una textura ya
+    // cargada (por ejemplo las de la sección [textures] de una escena) en vez de una ruta
+    pub fn from_defs_with_textures(
+        defs: &HashMap<String, MaterialDef>,
+        base_dir: &Path,
+        texture_manager: &mut TextureManager,
+        named_textures: &HashMap<String, usize>,
+    ) -> Result<Self, String> {
+        let mut builder = LibraryBuilder {
+            defs,
+            base_dir,
+            texture_manager,
+            named_textures,
+            loaded_textures: HashMap::new(),
+            resolved: HashMap::new(),
+        };
+
+        let mut names: Vec<&String> = defs.keys().collect();
+        names.sort(); // errores e ids deterministas
+        for name in &names {
+            builder.resolve(name, &mut Vec::new())?;
+        }
+
+        let mut library = MaterialLibrary::new();
+        for name in names {
+            library.insert(name, builder.resolved[name.as_str()])?;
+        }
+        Ok(library)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Material> {
+        self.id(name).map(|id| self.materials[id as usize])
+    }
+
+    pub fn id(&self, name: &str) -> Option<MaterialId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: MaterialId) -> Option<&str> {
+        self.ids.iter().find(|(_, other)| **other == id).map(|(name, _)| name.as_str())
+    }
+
+    // Agrega (o reemplaza) un material y devuelve su índice en la tabla
+    pub fn insert(&mut self, name: &str, material: Material) -> Result<MaterialId, String> {
+        if let Some(id) = self.id(name) {
+            self.materials[id as usize] = material;
+            return Ok(id);
+        }
+        let id = MaterialId::try_from(self.materials.len())
+            .map_err(|_| format!("too many materials (max {})", MaterialId::MAX as usize + 1))?;
+        self.materials.push(material);
+        self.ids.insert(name.to_string(), id);
+        Ok(id)
+    }
+
+    pub fn materials(&self) -> &[Material] {
+        &self.materials
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.ids.keys().map(|name| name.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.materials.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.materials.is_empty()
+    }
+}
+
+struct LibraryBuilder<'a> {
+    defs: &'a HashMap<String, MaterialDef>,
+    base_dir: &'a Path,
+    texture_manager: &'a mut TextureManager,
+    named_textures: &'a HashMap<String, usize>,
+    loaded_textures: HashMap<PathBuf, usize>, // cada archivo se carga una sola vez
+    resolved: HashMap<String, Material>,
+}
+
+impl LibraryBuilder<'_> {
+    fn resolve(&mut self, name: &str, stack: &mut Vec<String>) -> Result<Material, String> {
+        if let Some(material) = self.resolved.get(name) {
+            return Ok(*material);
+        }
+        if stack.iter().any(|n| n == name) {
+            return Err(format!("material '{}': 'extends' forms a cycle ({} -> {})", name, stack.join(" -> "), name));
+        }
+        let def = self.defs.get(name).ok_or_else(|| format!("unknown material '{}'", name))?;
+
+        stack.push(name.to_string());
+        let base = match &def.extends {
+            Some(parent) => {
+                if !self.defs.contains_key(parent) {
+                    return Err(format!("material '{}': field 'extends' names unknown material '{}'", name, parent));
+                }
+                Some(self.resolve(parent, stack)?)
+            }
+            None => None,
+        };
+        stack.pop();
+
+        let material = self.build(name, def, base)?;
+        self.resolved.insert(name.to_string(), material);
+        Ok(material)
+    }
+
+    fn build(&mut self, name: &str, def: &MaterialDef, base: Option<Material>) -> Result<Material, String> {
+        let field_err = |field: &str, message: String| format!("material '{}': field '{}' {}", name, field, message);
+
+        let model = match def.model.as_deref() {
+            None => None,
+            Some("phong") => Some(ShadingModel::Phong),
+            Some("pbr") => Some(ShadingModel::Pbr),
+            Some(other) => return Err(field_err("model", format!("must be \"phong\" or \"pbr\" (got \"{}\")", other))),
+        };
+
+        let mut material = match (base, model) {
+            (Some(base), _) => base,
+            (None, Some(ShadingModel::Pbr)) => Material::pbr(Vector3::one(), 0.0, 0.5),
+            (None, _) => Material::new(Vector3::one(), [1.0, 0.0], 1.0),
+        };
+        if let Some(model) = model {
+            material.model = model;
+        }
+
+        if let Some(d) = def.diffuse {
+            for (i, c) in d.iter().enumerate() {
+                check_range(*c, 0.0, 1.0).map_err(|m| field_err(&format!("diffuse[{}]", i), m))?;
+            }
+            material.diffuse = Vector3::new(d[0], d[1], d[2]);
+        }
+        if let Some(a) = def.albedo {
+            for (i, c) in a.iter().enumerate() {
+                check_range(*c, 0.0, 1.0).map_err(|m| field_err(&format!("albedo[{}]", i), m))?;
+            }
+            material.albedo = a;
+        }
+        if let Some(v) = def.specular {
+            check_range(v, 0.0, f32::MAX).map_err(|m| field_err("specular", m))?;
+            material.specular = v;
+        }
+        if let Some(v) = def.metallic {
+            check_range(v, 0.0, 1.0).map_err(|m| field_err("metallic", m))?;
+            material.metallic = v;
+        }
+        if let Some(v) = def.roughness {
+            check_range(v, 0.0, 1.0).map_err(|m| field_err("roughness", m))?;
+            material.roughness = v;
+        }
+        if let Some(v) = def.gloss_samples {
+            if v == 0 {
+                return Err(field_err("gloss_samples", "must be at least 1".to_string()));
+            }
+            material.gloss_samples = v;
+        }
+
+        if let Some(texture) = &def.texture {
+            material.texture_id = Some(self.texture(texture).map_err(|m| field_err("texture", m))?);
+        }
+        if let Some(blend) = &def.texture_blend {
+            material.texture_blend = match blend {
+                TextureBlendDef::Name(n) if n == "replace" => TextureBlend::Replace,
+                TextureBlendDef::Name(n) if n == "multiply" => TextureBlend::Multiply,
+                TextureBlendDef::Name(n) if n == "overlay" => TextureBlend::Overlay,
+                TextureBlendDef::Name(n) => {
+                    return Err(field_err(
+                        "texture_blend",
+                        format!("must be \"replace\", \"multiply\", \"overlay\" or {{ lerp = t }} (got \"{}\")", n),
+                    ));
+                }
+                TextureBlendDef::Lerp { lerp } => {
+                    check_range(*lerp, 0.0, 1.0).map_err(|m| field_err("texture_blend.lerp", m))?;
+                    TextureBlend::Lerp(*lerp)
+                }
+            };
+        }
+        if let Some(t) = &def.uv_transform {
+            // con `extends` los campos que no se indican se heredan del padre
+            let mut transform = material.uv_transform;
+            let pair = |field: &str, v: [f32; 2]| -> Result<Vector2, String> {
+                for (i, c) in v.iter().enumerate() {
+                    check_finite(*c).map_err(|m| field_err(&format!("uv_transform.{}[{}]", field, i), m))?;
+                }
+                Ok(Vector2::new(v[0], v[1]))
+            };
+            if let Some(o) = t.offset {
+                transform.offset = pair("offset", o)?;
+            }
+            if let Some(s) = t.scale {
+                if s[0] == 0.0 || s[1] == 0.0 {
+                    return Err(field_err("uv_transform.scale", "must not be zero".to_string()));
+                }
+                transform.scale = pair("scale", s)?;
+            }
+            if let Some(r) = t.rotation {
+                check_finite(r).map_err(|m| field_err("uv_transform.rotation", m))?;
+                transform.rotation = r;
+            }
+            if let Some(v) = t.offset_velocity {
+                transform.offset_velocity = pair("offset_velocity", v)?;
+            }
+            if let Some(w) = t.rotation_velocity {
+                check_finite(w).map_err(|m| field_err("uv_transform.rotation_velocity", m))?;
+                transform.rotation_velocity = w;
+            }
+            material.uv_transform = transform;
+        }
+
+        if let Some(texture) = &def.normal_map {
+            material.normal_map_id = Some(self.texture(texture).map_err(|m| field_err("normal_map", m))?);
+        }
+        if let Some(v) = def.normal_strength {
+            check_range(v, 0.0, f32::MAX).map_err(|m| field_err("normal_strength", m))?;
+            material.normal_strength = v;
+        }
+        if let Some(texture) = &def.height_map {
+            material.height_map_id = Some(self.texture(texture).map_err(|m| field_err("height_map", m))?);
+            if material.parallax_steps == 0 {
+                material.parallax_steps = 16;
+            }
+        }
+        if let Some(v) = def.parallax_scale {
+            check_range(v, 0.0, 1.0).map_err(|m| field_err("parallax_scale", m))?;
+            material.parallax_scale = v;
+        }
+        if let Some(v) = def.parallax_steps {
+            if v == 0 {
+                return Err(field_err("parallax_steps", "must be at least 1".to_string()));
+            }
+            material.parallax_steps = v;
+        }
+
+        if let Some(v) = def.transparency {
+            check_range(v, 0.0, 1.0).map_err(|m| field_err("transparency", m))?;
+            material.transparency = v;
+        }
+        if let Some(v) = def.reflectivity {
+            check_range(v, 0.0, 1.0).map_err(|m| field_err("reflectivity", m))?;
+            material.reflectivity = v;
+        }
+        if material.transparency + material.reflectivity > 1.0 {
+            return Err(field_err(
+                "reflectivity",
+                format!(
+                    "plus transparency must not exceed 1 (got {} + {})",
+                    material.reflectivity, material.transparency
+                ),
+            ));
+        }
+        if let Some(v) = def.ior {
+            check_range(v, 1.0, 4.0).map_err(|m| field_err("ior", m))?;
+            material.ior = v;
+        }
+        if let Some(v) = def.secondary_rays {
+            material.secondary_rays = v;
+        }
+        if let Some(w) = &def.waves {
+            check_range(w.amplitude, 0.0, f32::MAX).map_err(|m| field_err("waves.amplitude", m))?;
+            if !(w.wavelength > 0.0 && w.wavelength.is_finite()) {
+                return Err(field_err("waves.wavelength", format!("must be a positive finite number (got {})", w.wavelength)));
+            }
+            let speed = w.speed.unwrap_or(1.0);
+            check_finite(speed).map_err(|m| field_err("waves.speed", m))?;
+            let direction = w.direction.unwrap_or(0.0);
+            check_finite(direction).map_err(|m| field_err("waves.direction", m))?;
+            material.waves = Some(
+                WaterWaves::new(w.amplitude, w.wavelength, speed).with_direction(direction),
+            );
+        }
+        if let Some(v) = def.translucency {
+            check_range(v, 0.0, 1.0).map_err(|m| field_err("translucency", m))?;
+            material.translucency = v;
+        }
+        if let Some(v) = def.alpha_cutoff {
+            check_range(v, 0.0, 1.0).map_err(|m| field_err("alpha_cutoff", m))?;
+            material.alpha_cutoff = v;
+        }
+
+        if let Some(e) = def.emission {
+            for (i, c) in e.iter().enumerate() {
+                check_range(*c, 0.0, 1.0).map_err(|m| field_err(&format!("emission[{}]", i), m))?;
+            }
+            material.emission = Vector3::new(e[0], e[1], e[2]);
+            if def.emission_strength.is_none() && material.emission_strength == 0.0 {
+                material.emission_strength = 1.0;
+            }
+        }
+        if let Some(v) = def.emission_strength {
+            check_range(v, 0.0, f32::MAX).map_err(|m| field_err("emission_strength", m))?;
+            material.emission_strength = v;
+        }
+        if let Some(texture) = &def.emission_texture {
+            material.emission_texture_id = Some(self.texture(texture).map_err(|m| field_err("emission_texture", m))?);
+        }
+
+        Ok(material)
+    }
+
+    fn texture(&mut self, def: &TextureDef) -> Result<usize, String> {
+        let path = match def {
+            TextureDef::Image(path) => path,
+            TextureDef::Procedural(procedural) => {
+                let texture = ProceduralTexture::from_def(procedural).map_err(|e| format!("is invalid: {}", e))?;
+                return Ok(self.texture_manager.add_procedural(texture));
+            }
+        };
+        if let Some(id) = self.named_textures.get(path) {
+            return Ok(*id);
+        }
+        let full_path = self.base_dir.join(path);
+        if let Some(id) = self.loaded_textures.get(&full_path) {
+            return Ok(*id);
+        }
+        let id = self
+            .texture_manager
+            .load_texture(&full_path.to_string_lossy())
+            .map_err(|e| format!("could not load \"{}\": {}", full_path.display(), e))?;
+        self.loaded_textures.insert(full_path, id);
+        Ok(id)
+    }
+}
+
+fn check_finite(value: f32) -> Result<(), String> {
+    if !value.is_finite() {
+        return Err(format!("must be a finite number (got {})", value));
+    }
+    Ok(())
+}
+
+pub(crate) fn check_range(value: f32, min: f32, max: f32) -> Result<(), String> {
+    check_finite(value)?;
+    if value < min || value > max {
+        if max == f32::MAX {
+            return Err(format!("must be at least {} (got {})", min, value));
+        }
+        return Err(format!("must be between {} and {} (got {})", min, max, value));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(source: &str) -> Result<MaterialLibrary, String> {
+        MaterialLibrary::from_toml_str(source, Path::new(""), &mut TextureManager::new())
+    }
+
+    #[test]
+    fn out_of_range_fields_are_named() {
+        let error = load("[glass]\ntransparency = 1.5").err().unwrap();
+        assert!(error.starts_with("material 'glass': field 'transparency'"), "{}", error);
+
+        let error = load("[water]\nuv_transform = { offset_velocity = [0.1, nan] }").err().unwrap();
+        assert!(error.contains("field 'uv_transform.offset_velocity[1]'"), "{}", error);
+
+        let error = load("[water]\nwaves = { amplitude = 0.1, wavelength = 1.0, speed = inf }").err().unwrap();
+        assert!(error.contains("field 'waves.speed'"), "{}", error);
+    }
+
+    #[test]
+    fn unknown_parent_and_cycles_are_rejected() {
+        let error = load("[grass_dark]\nextends = \"gras\"").err().unwrap();
+        assert!(error.contains("unknown material 'gras'"), "{}", error);
+
+        let error = load("[a]\nextends = \"b\"\n[b]\nextends = \"c\"\n[c]\nextends = \"a\"").err().unwrap();
+        assert!(error.contains("forms a cycle"), "{}", error);
+    }
+
+    #[test]
+    fn extends_inherits_unspecified_uv_transform_fields() {
+        let source = r#"
+            [water]
+            uv_transform = { scale = [2.0, 2.0], offset_velocity = [0.1, 0.0] }
+
+            [fast_water]
+            extends = "water"
+            uv_transform = { offset_velocity = [0.3, 0.0] }
+        "#;
+        let library = load(source).unwrap();
+        let transform = library.get("fast_water").unwrap().uv_transform;
+        assert_eq!((transform.scale.x, transform.scale.y), (2.0, 2.0));
+        assert_eq!(transform.offset_velocity.x, 0.3);
+    }
+}
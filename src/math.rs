@@ -0,0 +1,212 @@
+// math.rs
+// Vectores y color propios con la misma API que usábamos de raylib, para que el
+// núcleo compile (y se pueda probar) sin enlazar la biblioteca de C
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vector2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vector3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+// RGBA de 8 bits por canal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Vector2 {
+    pub const fn new(x: f32, y: f32) -> Self {
+        Vector2 { x, y }
+    }
+
+    pub const fn zero() -> Self {
+        Vector2::new(0.0, 0.0)
+    }
+
+    pub const fn one() -> Self {
+        Vector2::new(1.0, 1.0)
+    }
+
+    pub fn dot(&self, v: Vector2) -> f32 {
+        self.x * v.x + self.y * v.y
+    }
+
+    pub fn length(&self) -> f32 {
+        self.length_sqr().sqrt()
+    }
+
+    pub fn length_sqr(&self) -> f32 {
+        self.dot(*self)
+    }
+
+    pub fn normalized(&self) -> Vector2 {
+        let length = self.length();
+        if length == 0.0 { *self } else { *self / length }
+    }
+}
+
+impl Vector3 {
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Vector3 { x, y, z }
+    }
+
+    pub const fn zero() -> Self {
+        Vector3::new(0.0, 0.0, 0.0)
+    }
+
+    pub const fn one() -> Self {
+        Vector3::new(1.0, 1.0, 1.0)
+    }
+
+    pub fn dot(&self, v: Vector3) -> f32 {
+        self.x * v.x + self.y * v.y + self.z * v.z
+    }
+
+    pub fn cross(&self, v: Vector3) -> Vector3 {
+        Vector3::new(
+            self.y * v.z - self.z * v.y,
+            self.z * v.x - self.x * v.z,
+            self.x * v.y - self.y * v.x,
+        )
+    }
+
+    pub fn length(&self) -> f32 {
+        self.length_sqr().sqrt()
+    }
+
+    pub fn length_sqr(&self) -> f32 {
+        self.dot(*self)
+    }
+
+    // Igual que raylib: el vector nulo se devuelve tal cual
+    pub fn normalized(&self) -> Vector3 {
+        let length = self.length();
+        if length == 0.0 { *self } else { *self / length }
+    }
+
+    pub fn lerp(&self, v: Vector3, amount: f32) -> Vector3 {
+        *self + (v - *self) * amount
+    }
+}
+
+impl Color {
+    pub const BLACK: Color = Color::new(0, 0, 0, 255);
+    pub const WHITE: Color = Color::new(255, 255, 255, 255);
+    pub const BLANK: Color = Color::new(0, 0, 0, 0);
+
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Color { r, g, b, a }
+    }
+}
+
+// Operadores componente a componente, con vector o con escalar
+macro_rules! impl_vector_ops {
+    ($vector:ident { $($field:ident),+ }) => {
+        impl Add for $vector {
+            type Output = $vector;
+            fn add(self, v: $vector) -> $vector {
+                $vector { $($field: self.$field + v.$field),+ }
+            }
+        }
+
+        impl Add<f32> for $vector {
+            type Output = $vector;
+            fn add(self, s: f32) -> $vector {
+                $vector { $($field: self.$field + s),+ }
+            }
+        }
+
+        impl Sub for $vector {
+            type Output = $vector;
+            fn sub(self, v: $vector) -> $vector {
+                $vector { $($field: self.$field - v.$field),+ }
+            }
+        }
+
+        impl Sub<f32> for $vector {
+            type Output = $vector;
+            fn sub(self, s: f32) -> $vector {
+                $vector { $($field: self.$field - s),+ }
+            }
+        }
+
+        impl Mul for $vector {
+            type Output = $vector;
+            fn mul(self, v: $vector) -> $vector {
+                $vector { $($field: self.$field * v.$field),+ }
+            }
+        }
+
+        impl Mul<f32> for $vector {
+            type Output = $vector;
+            fn mul(self, s: f32) -> $vector {
+                $vector { $($field: self.$field * s),+ }
+            }
+        }
+
+        impl Div for $vector {
+            type Output = $vector;
+            fn div(self, v: $vector) -> $vector {
+                $vector { $($field: self.$field / v.$field),+ }
+            }
+        }
+
+        impl Div<f32> for $vector {
+            type Output = $vector;
+            fn div(self, s: f32) -> $vector {
+                $vector { $($field: self.$field / s),+ }
+            }
+        }
+
+        impl Neg for $vector {
+            type Output = $vector;
+            fn neg(self) -> $vector {
+                $vector { $($field: -self.$field),+ }
+            }
+        }
+
+        impl AddAssign for $vector {
+            fn add_assign(&mut self, v: $vector) {
+                *self = *self + v;
+            }
+        }
+
+        impl SubAssign for $vector {
+            fn sub_assign(&mut self, v: $vector) {
+                *self = *self - v;
+            }
+        }
+
+        impl MulAssign for $vector {
+            fn mul_assign(&mut self, v: $vector) {
+                *self = *self * v;
+            }
+        }
+
+        impl MulAssign<f32> for $vector {
+            fn mul_assign(&mut self, s: f32) {
+                *self = *self * s;
+            }
+        }
+
+        impl DivAssign<f32> for $vector {
+            fn div_assign(&mut self, s: f32) {
+                *self = *self / s;
+            }
+        }
+    };
+}
+
+impl_vector_ops!(Vector2 { x, y });
+impl_vector_ops!(Vector3 { x, y, z });
@@ -0,0 +1,69 @@
+// pbr.rs
+// Modelo metálico/rugosidad: GGX (Trowbridge-Reitz) + Smith (Schlick-GGX) + Fresnel de Schlick
+use crate::math::Vector3;
+use std::f32::consts::PI;
+
+// Reflectancia a incidencia normal de los dieléctricos comunes
+pub const DIELECTRIC_F0: f32 = 0.04;
+
+pub fn base_reflectance(base_color: Vector3, metallic: f32) -> Vector3 {
+    let dielectric = Vector3::new(DIELECTRIC_F0, DIELECTRIC_F0, DIELECTRIC_F0);
+    dielectric * (1.0 - metallic) + base_color * metallic
+}
+
+pub fn fresnel_schlick(cos_theta: f32, f0: Vector3) -> Vector3 {
+    let factor = (1.0 - cos_theta.clamp(0.0, 1.0)).powi(5);
+    f0 + (Vector3::one() - f0) * factor
+}
+
+// Distribución de normales GGX
+pub fn distribution_ggx(n_dot_h: f32, roughness: f32) -> f32 {
+    let alpha = roughness * roughness;
+    let alpha2 = alpha * alpha;
+    let denom = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+    alpha2 / (PI * denom * denom).max(1e-7)
+}
+
+// Sombreado/enmascarado de Smith con la aproximación de Schlick-GGX para luz puntual
+pub fn geometry_smith(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+    let r = roughness + 1.0;
+    let k = r * r / 8.0;
+    let g1 = |x: f32| x / (x * (1.0 - k) + k);
+    g1(n_dot_v) * g1(n_dot_l)
+}
+
+// Radiancia reflejada hacia `view` por una luz puntual desde `light_direction`.
+// `radiance` es color * intensidad de la luz; se usa la convención de luces puntuales
+// (se multiplica por PI) para que un blanco lambertiano dé lo mismo que el difuso Phong.
+pub fn direct_lighting(
+    normal: Vector3,
+    view: Vector3,
+    light_direction: Vector3,
+    radiance: Vector3,
+    base_color: Vector3,
+    metallic: f32,
+    roughness: f32,
+) -> Vector3 {
+    let n_dot_l = normal.dot(light_direction);
+    let n_dot_v = normal.dot(view);
+    if n_dot_l <= 0.0 || n_dot_v <= 0.0 {
+        return Vector3::zero();
+    }
+
+    let roughness = roughness.clamp(0.04, 1.0); // evita picos infinitos en superficies espejo
+    let half = (view + light_direction).normalized();
+    let n_dot_h = normal.dot(half).max(0.0);
+    let v_dot_h = view.dot(half).max(0.0);
+
+    let f0 = base_reflectance(base_color, metallic);
+    let fresnel = fresnel_schlick(v_dot_h, f0);
+    let d = distribution_ggx(n_dot_h, roughness);
+    let g = geometry_smith(n_dot_v, n_dot_l, roughness);
+    let specular = fresnel * (d * g / (4.0 * n_dot_v * n_dot_l).max(1e-4));
+
+    // lo que no refleja el Fresnel entra como difuso (los metales no tienen difuso)
+    let k_diffuse = (Vector3::one() - fresnel) * (1.0 - metallic);
+    let diffuse = k_diffuse * base_color / PI;
+
+    (diffuse + specular) * radiance * (n_dot_l * PI)
+}
@@ -0,0 +1,271 @@
+// prefab.rs
+// Estructuras reutilizables (árboles, casas, rocas...) descritas como capas de máscaras
+// de voxeles, de abajo hacia arriba:
+//
+//     anchor = [1.0, 0.0, 1.0]          # punto (x, y, z) en bloques que cae en la posición
+//
+//     [legend]
+//     w = { material = "wood", size = 0.9 }   # size: lado del cubo en bloques (1 por defecto)
+//     l = { material = "leaves" }
+//
+//     [[layers]]
+//     height = 0.9                      # espesor de la capa en bloques (1 por defecto)
+//     rows = """
+//     ...
+//     .w.
+//     ...
+//     """
+//
+//     [[layers]]
+//     offset = [0.0, -0.5]              # corrimiento (x, z) de la capa en bloques
+//     rows = """
+//     .l.
+//     lll
+//     """
+//
+// La celda (columna, fila) de una capa tiene su centro en x = columna, z = fila.
+// Mapas y terreno colocan el prefab en el centro del bloque que lo sostiene, así que las
+// coordenadas del prefab son relativas a ese centro: con anchor y = 0 la primera capa
+// empieza a media altura del bloque de apoyo.
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::cube::Cube;
+use crate::material_library::MaterialLibrary;
+use crate::math::Vector3;
+use crate::primitive::Primitive;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PrefabDef {
+    pub anchor: Option<[f32; 3]>,
+    pub legend: HashMap<String, PrefabBlockDef>,
+    pub layers: Vec<PrefabLayerDef>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PrefabBlockDef {
+    pub material: String,
+    pub size: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PrefabLayerDef {
+    pub height: Option<f32>,
+    pub offset: Option<[f32; 2]>,
+    pub rows: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct PrefabBlock {
+    pub material: String,
+    pub size: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct PrefabLayer {
+    pub height: f32,
+    pub offset: [f32; 2],
+    pub rows: Vec<Vec<char>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Prefab {
+    pub anchor: Vector3,
+    pub legend: HashMap<char, PrefabBlock>,
+    pub layers: Vec<PrefabLayer>,
+}
+
+// Los cubos no giran, así que solo se admiten cuartos de vuelta alrededor de y
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    pub position: Vector3,
+    pub quarter_turns: u32,          // giro antihorario visto desde arriba, en pasos de 90°
+    pub mirror_x: bool,              // espejo sobre el eje x (antes de girar)
+    pub mirror_z: bool,
+    pub scale: f32,                  // tamaño de un bloque del prefab en el mundo
+}
+
+impl Placement {
+    pub fn at(position: Vector3) -> Self {
+        Placement {
+            position,
+            quarter_turns: 0,
+            mirror_x: false,
+            mirror_z: false,
+            scale: 1.0,
+        }
+    }
+
+    pub fn with_rotation(mut self, degrees: i32) -> Result<Self, String> {
+        if degrees % 90 != 0 {
+            return Err(format!("rotation must be a multiple of 90 degrees (got {})", degrees));
+        }
+        self.quarter_turns = (degrees / 90).rem_euclid(4) as u32;
+        Ok(self)
+    }
+
+    // "x", "z" o "xz"
+    pub fn with_mirror(mut self, axes: &str) -> Result<Self, String> {
+        if axes.is_empty() || !axes.chars().all(|c| c == 'x' || c == 'z') {
+            return Err(format!("mirror must be \"x\", \"z\" or \"xz\" (got \"{}\")", axes));
+        }
+        self.mirror_x = axes.contains('x');
+        self.mirror_z = axes.contains('z');
+        Ok(self)
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    // Lleva un desplazamiento (x, z) del prefab al mundo
+    fn transform(&self, x: f32, z: f32) -> (f32, f32) {
+        let x = if self.mirror_x { -x } else { x };
+        let z = if self.mirror_z { -z } else { z };
+        match self.quarter_turns {
+            1 => (z, -x),
+            2 => (-x, -z),
+            3 => (-z, x),
+            _ => (x, z),
+        }
+    }
+}
+
+impl Prefab {
+    pub fn load(file_path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(file_path)
+            .map_err(|e| format!("{}: {}", file_path, e))?;
+        Self::from_toml_str(&source).map_err(|e| format!("{}: {}", file_path, e))
+    }
+
+    pub fn from_toml_str(source: &str) -> Result<Self, String> {
+        let def: PrefabDef = toml::from_str(source).map_err(|e| e.to_string())?;
+        Self::from_def(def)
+    }
+
+    pub fn from_def(def: PrefabDef) -> Result<Self, String> {
+        let mut legend = HashMap::new();
+        for (key, block) in def.legend {
+            let mut chars = key.chars();
+            let ch = match (chars.next(), chars.next()) {
+                (Some(ch), None) if ch != ' ' && ch != '.' => ch,
+                _ => return Err(format!("legend key \"{}\" must be a single character other than ' ' or '.'", key)),
+            };
+            let size = block.size.unwrap_or(1.0);
+            if !(size > 0.0 && size.is_finite()) {
+                return Err(format!("legend '{}': field 'size' must be positive (got {})", ch, size));
+            }
+            legend.insert(ch, PrefabBlock { material: block.material, size });
+        }
+
+        let mut layers = Vec::with_capacity(def.layers.len());
+        for (i, layer) in def.layers.into_iter().enumerate() {
+            let height = layer.height.unwrap_or(1.0);
+            if !(height > 0.0 && height.is_finite()) {
+                return Err(format!("layer {}: field 'height' must be positive (got {})", i, height));
+            }
+            let rows: Vec<Vec<char>> = layer.rows.lines().map(|line| line.trim_end().chars().collect()).collect();
+            for (r, row) in rows.iter().enumerate() {
+                for (c, ch) in row.iter().enumerate() {
+                    if *ch != ' ' && *ch != '.' && !legend.contains_key(ch) {
+                        return Err(format!(
+                            "layer {}, row {}, column {}: character '{}' is not in the legend",
+                            i, r + 1, c + 1, ch
+                        ));
+                    }
+                }
+            }
+            layers.push(PrefabLayer { height, offset: layer.offset.unwrap_or([0.0, 0.0]), rows });
+        }
+
+        let anchor = def.anchor.unwrap_or([0.0, 0.0, 0.0]);
+        Ok(Prefab {
+            anchor: Vector3::new(anchor[0], anchor[1], anchor[2]),
+            legend,
+            layers,
+        })
+    }
+
+    // Agrega los cubos del prefab a `objects`
+    pub fn place(
+        &self,
+        placement: &Placement,
+        materials: &MaterialLibrary,
+        objects: &mut Vec<Primitive>,
+    ) -> Result<(), String> {
+        let scale = placement.scale;
+        let mut bottom = placement.position.y - self.anchor.y * scale;
+
+        for layer in &self.layers {
+            let height = layer.height * scale;
+            let y = bottom + height * 0.5;
+            for (r, row) in layer.rows.iter().enumerate() {
+                for (c, ch) in row.iter().enumerate() {
+                    let Some(block) = self.legend.get(ch) else {
+                        continue;
+                    };
+                    let material_id = materials
+                        .id(&block.material)
+                        .ok_or_else(|| format!("legend '{}': unknown material '{}'", ch, block.material))?;
+
+                    let (dx, dz) = placement.transform(
+                        (c as f32 + layer.offset[0] - self.anchor.x) * scale,
+                        (r as f32 + layer.offset[1] - self.anchor.z) * scale,
+                    );
+                    let center = Vector3::new(placement.position.x + dx, y, placement.position.z + dz);
+                    objects.push(Cube { center, size: block.size * scale, material_id }.into());
+                }
+            }
+            bottom += height;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Material;
+
+    #[test]
+    fn placement_mirrors_then_turns_counterclockwise() {
+        let at = Placement::at(Vector3::zero());
+        assert_eq!(at.transform(1.0, 2.0), (1.0, 2.0));
+
+        // visto desde arriba (-y) con x a la derecha, +x gira hacia -z
+        let turned = |degrees| at.with_rotation(degrees).unwrap().transform(1.0, 2.0);
+        assert_eq!(turned(90), (2.0, -1.0));
+        assert_eq!(turned(180), (-1.0, -2.0));
+        assert_eq!(turned(270), (-2.0, 1.0));
+        assert_eq!(turned(-90), turned(270));
+        assert_eq!(turned(360), (1.0, 2.0));
+        assert!(at.with_rotation(45).is_err());
+
+        // el espejo se aplica antes del giro
+        let mirrored = at.with_mirror("x").unwrap();
+        assert_eq!(mirrored.transform(1.0, 2.0), (-1.0, 2.0));
+        assert_eq!(mirrored.with_rotation(90).unwrap().transform(1.0, 2.0), (2.0, 1.0));
+        assert_eq!(at.with_mirror("xz").unwrap().transform(1.0, 2.0), turned(180));
+        assert!(at.with_mirror("y").is_err());
+    }
+
+    #[test]
+    fn placed_blocks_follow_rotation_and_anchor() {
+        // las filas no se recortan a la izquierda: van sin sangría
+        let source = "anchor = [1.0, 0.0, 1.0]\nlegend = { w = { material = \"wood\" } }\n[[layers]]\nrows = \"...\\n.ww\\n...\"";
+        let prefab = Prefab::from_toml_str(source).unwrap();
+        let mut materials = MaterialLibrary::new();
+        materials.insert("wood", Material::new(Vector3::one(), [1.0, 0.0], 1.0)).unwrap();
+
+        let mut objects = Vec::new();
+        let placement = Placement::at(Vector3::new(10.0, 0.0, 0.0)).with_rotation(90).unwrap();
+        prefab.place(&placement, &materials, &mut objects).unwrap();
+        let centers: Vec<Vector3> = objects.iter().map(|object| object.center()).collect();
+        // el bloque del ancla se queda en la posición; el de su derecha (+x) pasa a -z
+        assert_eq!(centers, vec![Vector3::new(10.0, 0.5, 0.0), Vector3::new(10.0, 0.5, -1.0)]);
+    }
+}
@@ -0,0 +1,50 @@
+// primitive.rs
+use crate::cube::Cube;
+use crate::material::MaterialId;
+use crate::math::Vector3;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use crate::sphere::Sphere;
+
+// Cualquier objeto que puede ir en una escena; así cubos y esferas comparten la misma lista
+#[derive(Debug, Clone, Copy)]
+pub enum Primitive {
+    Cube(Cube),
+    Sphere(Sphere),
+}
+
+impl Primitive {
+    pub fn center(&self) -> Vector3 {
+        match self {
+            Primitive::Cube(cube) => cube.center,
+            Primitive::Sphere(sphere) => sphere.center,
+        }
+    }
+
+    pub fn material_id(&self) -> MaterialId {
+        match self {
+            Primitive::Cube(cube) => cube.material_id,
+            Primitive::Sphere(sphere) => sphere.material_id,
+        }
+    }
+}
+
+impl RayIntersect for Primitive {
+    fn ray_intersect(&self, ray_origin: &Vector3, ray_direction: &Vector3) -> Intersect {
+        match self {
+            Primitive::Cube(cube) => cube.ray_intersect(ray_origin, ray_direction),
+            Primitive::Sphere(sphere) => sphere.ray_intersect(ray_origin, ray_direction),
+        }
+    }
+}
+
+impl From<Cube> for Primitive {
+    fn from(cube: Cube) -> Self {
+        Primitive::Cube(cube)
+    }
+}
+
+impl From<Sphere> for Primitive {
+    fn from(sphere: Sphere) -> Self {
+        Primitive::Sphere(sphere)
+    }
+}
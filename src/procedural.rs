@@ -0,0 +1,268 @@
+// procedural.rs
+use crate::math::{Color, Vector2, Vector3};
+use crate::material::vector3_to_color;
+use serde::Deserialize;
+
+// Ruido de gradiente (Perlin mejorado) con tabla de permutación según la semilla
+#[derive(Debug, Clone)]
+pub struct Perlin {
+    perm: [u8; 512],
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut table: [u8; 256] = std::array::from_fn(|i| i as u8);
+
+        // Fisher-Yates con splitmix64: la misma semilla da siempre la misma tabla
+        let mut state = seed;
+        for i in (1..256).rev() {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^= z >> 31;
+            table.swap(i, (z % (i as u64 + 1)) as usize);
+        }
+
+        let mut perm = [0u8; 512];
+        for i in 0..512 {
+            perm[i] = table[i & 255];
+        }
+        Perlin { perm }
+    }
+
+    // Valor en [-1, 1] aprox.
+    pub fn noise(&self, p: Vector3) -> f32 {
+        let xf = p.x.floor();
+        let yf = p.y.floor();
+        let zf = p.z.floor();
+        let xi = (xf as i32 & 255) as usize;
+        let yi = (yf as i32 & 255) as usize;
+        let zi = (zf as i32 & 255) as usize;
+        let x = p.x - xf;
+        let y = p.y - yf;
+        let z = p.z - zf;
+
+        let u = fade(x);
+        let v = fade(y);
+        let w = fade(z);
+
+        let perm = &self.perm;
+        let a = perm[xi] as usize + yi;
+        let aa = perm[a] as usize + zi;
+        let ab = perm[a + 1] as usize + zi;
+        let b = perm[xi + 1] as usize + yi;
+        let ba = perm[b] as usize + zi;
+        let bb = perm[b + 1] as usize + zi;
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, grad(perm[aa], x, y, z), grad(perm[ba], x - 1.0, y, z)),
+                lerp(u, grad(perm[ab], x, y - 1.0, z), grad(perm[bb], x - 1.0, y - 1.0, z)),
+            ),
+            lerp(
+                v,
+                lerp(u, grad(perm[aa + 1], x, y, z - 1.0), grad(perm[ba + 1], x - 1.0, y, z - 1.0)),
+                lerp(u, grad(perm[ab + 1], x, y - 1.0, z - 1.0), grad(perm[bb + 1], x - 1.0, y - 1.0, z - 1.0)),
+            ),
+        )
+    }
+
+    // Suma de octavas normalizada a [-1, 1] aprox.
+    pub fn fbm(&self, p: Vector3, octaves: u32, lacunarity: f32, gain: f32) -> f32 {
+        let mut sum = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        let mut norm = 0.0;
+        for _ in 0..octaves.max(1) {
+            sum += self.noise(p * frequency) * amplitude;
+            norm += amplitude;
+            amplitude *= gain;
+            frequency *= lacunarity;
+        }
+        sum / norm
+    }
+
+    // Turbulencia: suma de |ruido|, en [0, 1] aprox.
+    pub fn turbulence(&self, p: Vector3, octaves: u32) -> f32 {
+        let mut sum = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        let mut norm = 0.0;
+        for _ in 0..octaves.max(1) {
+            sum += self.noise(p * frequency).abs() * amplitude;
+            norm += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        sum / norm
+    }
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    a + t * (b - a)
+}
+
+fn grad(hash: u8, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 { y } else if h == 12 || h == 14 { x } else { z };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+// Dónde se evalúa el patrón: en la UV de la cara o en el punto 3D del impacto
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureSpace {
+    Uv,
+    World,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pattern {
+    Checker { size: f32 },                                                    // casillas por unidad
+    Noise { frequency: f32 },
+    Fbm { frequency: f32, octaves: u32, lacunarity: f32, gain: f32 },
+    Marble { frequency: f32, turbulence: f32, octaves: u32 },                 // vetas a lo largo de x
+    Wood { rings: f32, turbulence: f32 },                                     // anillos alrededor del eje y (en UV, del origen)
+}
+
+// { pattern = "checker" | "noise" | "fbm" | "marble" | "wood", ... } en una escena o biblioteca
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProceduralDef {
+    pub pattern: String,
+    pub seed: Option<u64>,
+    pub colors: Option<[[f32; 3]; 2]>,
+    pub space: Option<String>,
+    pub scale: Option<f32>,
+    pub size: Option<f32>,
+    pub frequency: Option<f32>,
+    pub octaves: Option<u32>,
+    pub lacunarity: Option<f32>,
+    pub gain: Option<f32>,
+    pub turbulence: Option<f32>,
+    pub rings: Option<f32>,
+}
+
+// Textura calculada al vuelo; se registra en el TextureManager como cualquier imagen
+#[derive(Debug, Clone)]
+pub struct ProceduralTexture {
+    pub pattern: Pattern,
+    pub space: TextureSpace,
+    pub color_a: Vector3,
+    pub color_b: Vector3,
+    pub scale: f32,                  // escala de las coordenadas antes de evaluar
+    perlin: Perlin,
+}
+
+impl ProceduralTexture {
+    pub fn new(pattern: Pattern, seed: u64) -> Self {
+        ProceduralTexture {
+            pattern,
+            space: TextureSpace::Uv,
+            color_a: Vector3::zero(),
+            color_b: Vector3::one(),
+            scale: 1.0,
+            perlin: Perlin::new(seed),
+        }
+    }
+
+    pub fn from_def(def: &ProceduralDef) -> Result<Self, String> {
+        let field_err = |field: &str, message: String| format!("field '{}' {}", field, message);
+
+        let frequency = def.frequency.unwrap_or(4.0);
+        let octaves = def.octaves.unwrap_or(5);
+        let turbulence = def.turbulence.unwrap_or(5.0);
+        let pattern = match def.pattern.as_str() {
+            "checker" => Pattern::Checker { size: def.size.unwrap_or(8.0) },
+            "noise" => Pattern::Noise { frequency },
+            "fbm" => Pattern::Fbm {
+                frequency,
+                octaves,
+                lacunarity: def.lacunarity.unwrap_or(2.0),
+                gain: def.gain.unwrap_or(0.5),
+            },
+            "marble" => Pattern::Marble { frequency, turbulence, octaves },
+            "wood" => Pattern::Wood {
+                rings: def.rings.unwrap_or(8.0),
+                turbulence: def.turbulence.unwrap_or(0.1),
+            },
+            other => {
+                return Err(field_err(
+                    "pattern",
+                    format!("must be \"checker\", \"noise\", \"fbm\", \"marble\" or \"wood\" (got \"{}\")", other),
+                ));
+            }
+        };
+
+        let space = match def.space.as_deref() {
+            None | Some("uv") => TextureSpace::Uv,
+            Some("world") => TextureSpace::World,
+            Some(other) => return Err(field_err("space", format!("must be \"uv\" or \"world\" (got \"{}\")", other))),
+        };
+        let scale = def.scale.unwrap_or(1.0);
+        if scale == 0.0 {
+            return Err(field_err("scale", "must not be zero".to_string()));
+        }
+
+        let mut texture = ProceduralTexture::new(pattern, def.seed.unwrap_or(0)).with_space(space, scale);
+        if let Some([a, b]) = def.colors {
+            texture = texture.with_colors(Vector3::new(a[0], a[1], a[2]), Vector3::new(b[0], b[1], b[2]));
+        }
+        Ok(texture)
+    }
+
+    pub fn with_colors(mut self, color_a: Vector3, color_b: Vector3) -> Self {
+        self.color_a = color_a;
+        self.color_b = color_b;
+        self
+    }
+
+    pub fn with_space(mut self, space: TextureSpace, scale: f32) -> Self {
+        self.space = space;
+        self.scale = scale;
+        self
+    }
+
+    // Valor del patrón en [0, 1]
+    pub fn value(&self, uv: Option<Vector2>, point: Vector3) -> f32 {
+        let (p, in_uv) = match (self.space, uv) {
+            (TextureSpace::Uv, Some(uv)) => (Vector3::new(uv.x, uv.y, 0.0), true),
+            _ => (point, false),
+        };
+        let p = p * self.scale;
+
+        let t = match self.pattern {
+            Pattern::Checker { size } => {
+                let sum = (p.x * size).floor() + (p.y * size).floor() + (p.z * size).floor();
+                if (sum as i32).rem_euclid(2) == 0 { 0.0 } else { 1.0 }
+            }
+            Pattern::Noise { frequency } => self.perlin.noise(p * frequency) * 0.5 + 0.5,
+            Pattern::Fbm { frequency, octaves, lacunarity, gain } => {
+                self.perlin.fbm(p * frequency, octaves, lacunarity, gain) * 0.5 + 0.5
+            }
+            Pattern::Marble { frequency, turbulence, octaves } => {
+                let phase = p.x * frequency + turbulence * self.perlin.turbulence(p, octaves);
+                phase.sin() * 0.5 + 0.5
+            }
+            Pattern::Wood { rings, turbulence } => {
+                // en UV el plano de la cara es (x, y); z siempre vale 0
+                let radius = if in_uv { (p.x * p.x + p.y * p.y).sqrt() } else { (p.x * p.x + p.z * p.z).sqrt() };
+                let r = radius * rings + turbulence * self.perlin.noise(p);
+                r - r.floor()
+            }
+        };
+        t.clamp(0.0, 1.0)
+    }
+
+    pub fn sample(&self, uv: Option<Vector2>, point: Vector3) -> Color {
+        let t = self.value(uv, point);
+        vector3_to_color(self.color_a * (1.0 - t) + self.color_b * t)
+    }
+}
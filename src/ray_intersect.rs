@@ -1,38 +1,44 @@
 // ray_intersect.rs
-use raylib::prelude::{Color, Vector2, Vector3}; // <-- Añade Vector2
-use crate::material::Material;
+use crate::math::{Vector2, Vector3}; // <-- Añade Vector2
+use crate::material::MaterialId;
 
 #[derive(Debug, Clone, Copy)]
 #[allow(dead_code)]
 pub struct Intersect {
-    pub material: Material,
+    pub material_id: MaterialId,     // se resuelve en la tabla solo para el impacto más cercano
     pub distance: f32,
     pub is_intersecting: bool,
     pub normal: Vector3,
     pub point: Vector3,
     pub uv: Option<Vector2>, // <-- Añade esta línea
+    pub tangent: Vector3,    // dirección de +u sobre la superficie (cero si no hay UV)
+    pub bitangent: Vector3,  // dirección de +v sobre la superficie
 }
 
 impl Intersect {
-    pub fn new(material: Material, distance: f32, normal: Vector3, point: Vector3, uv: Option<Vector2>) -> Self {
+    pub fn new(material_id: MaterialId, distance: f32, normal: Vector3, point: Vector3, uv: Option<Vector2>) -> Self {
         Intersect {
-            material,
+            material_id,
             distance,
             is_intersecting: true,
             normal,
             point,
             uv, // <-- Añade esta línea
+            tangent: Vector3::zero(),
+            bitangent: Vector3::zero(),
         }
     }
 
     pub fn empty() -> Self {
         Intersect {
-            material: Material::black(),
+            material_id: 0,
             distance: 0.0,
             is_intersecting: false,
             normal: Vector3::zero(),
             point: Vector3::zero(),
             uv: None, // <-- Añade esta línea
+            tangent: Vector3::zero(),
+            bitangent: Vector3::zero(),
         }
     }
 }
@@ -0,0 +1,594 @@
+// render.rs
+// Se conserva el estilo original del trazador (ifs anidados, `a = a * b`)
+#![allow(clippy::collapsible_if, clippy::assign_op_pattern)]
+use crate::math::{Color, Vector2, Vector3};
+
+use crate::camera::Camera;
+use crate::framebuffer::Framebuffer;
+use crate::light::Light;
+use crate::material::{Material, ShadingModel, vector3_to_color, color_to_vector3};
+use crate::pbr;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use crate::sampling::{Rng, glossy_direction};
+use crate::texture::TextureManager;
+
+pub const MAX_RAY_DEPTH: u32 = 3;
+// Cielo por defecto de las escenas
+pub const SKYBOX_COLOR: Color = Color::new(204, 184, 204, 255);
+
+fn reflect(incident: &Vector3, normal: &Vector3) -> Vector3 {
+    *incident - *normal * 2.0 * incident.dot(*normal)
+}
+
+// Ley de Snell; None si hay reflexión total interna
+fn refract(incident: &Vector3, normal: &Vector3, ior: f32) -> Option<Vector3> {
+    let mut cosi = incident.dot(*normal).clamp(-1.0, 1.0);
+    let mut n = *normal;
+    let (mut eta_i, mut eta_t) = (1.0, ior);
+
+    if cosi < 0.0 {
+        cosi = -cosi;   // entrando al material
+    } else {
+        std::mem::swap(&mut eta_i, &mut eta_t);   // saliendo
+        n = -n;
+    }
+
+    let eta = eta_i / eta_t;
+    let k = 1.0 - eta * eta * (1.0 - cosi * cosi);
+    if k < 0.0 {
+        None
+    } else {
+        Some(*incident * eta + n * (eta * cosi - k.sqrt()))
+    }
+}
+
+// Origen de un rayo secundario, separado de la superficie hacia el lado al que va
+fn offset_origin(intersect: &Intersect, direction: &Vector3) -> Vector3 {
+    let offset = intersect.normal * 1e-4;
+    if direction.dot(intersect.normal) < 0.0 {
+        intersect.point - offset
+    } else {
+        intersect.point + offset
+    }
+}
+
+// true si el texel bajo el impacto es transparente según el recorte alfa del material
+fn is_cut_out(intersect: &Intersect, material: &Material, texture_manager: &TextureManager) -> bool {
+    if material.alpha_cutoff <= 0.0 {
+        return false;
+    }
+
+    if let Some(texture_id) = material.texture_id {
+        if let Some(texel) = texture_manager.sample(texture_id, intersect.uv, intersect.point) {
+            let alpha = texel.a as f32 / 255.0;
+            return alpha < material.alpha_cutoff;
+        }
+    }
+    false
+}
+
+// Aplica la transformación de UV animada del material en el instante `time`.
+// El marco tangente se gira igual para que los normal maps sigan a la textura
+fn animate_uv(intersect: &mut Intersect, material: &Material, time: f32) {
+    let transform = material.uv_transform;
+    if transform.is_identity() {
+        return;
+    }
+
+    if let Some(uv) = intersect.uv {
+        intersect.uv = Some(transform.apply(uv, time));
+
+        let (sin, cos) = transform.angle(time).sin_cos();
+        let tangent = intersect.tangent * cos - intersect.bitangent * sin;
+        let bitangent = intersect.tangent * sin + intersect.bitangent * cos;
+        intersect.tangent = tangent;
+        intersect.bitangent = bitangent;
+    }
+}
+
+// UV finales del impacto: animadas y desplazadas por el parallax
+fn resolve_uv(intersect: &mut Intersect, material: &Material, ray_direction: &Vector3, texture_manager: &TextureManager, time: f32) {
+    animate_uv(intersect, material, time);
+    let view_direction = -ray_direction.normalized();
+    if let Some(uv) = parallax_uv(intersect, material, &view_direction, texture_manager) {
+        intersect.uv = Some(uv);
+    }
+}
+
+// Intersección que respeta el recorte alfa: si la cara de entrada cae en un texel
+// transparente, el rayo sigue y puede chocar con la cara interior del mismo bloque.
+// Solo los materiales con recorte resuelven aquí sus UV (el recorte tiene que mirar el
+// mismo texel que luego se pinta); el resto lo hace `cast_ray` en el impacto más cercano
+fn intersect_with_cutout<T: RayIntersect>(
+    object: &T,
+    ray_origin: &Vector3,
+    ray_direction: &Vector3,
+    materials: &[Material],
+    texture_manager: &TextureManager,
+    time: f32,
+) -> Intersect {
+    let mut hit = object.ray_intersect(ray_origin, ray_direction);
+    if !hit.is_intersecting {
+        return hit;
+    }
+    let material = &materials[hit.material_id as usize];
+    if material.alpha_cutoff <= 0.0 {
+        return hit;
+    }
+    resolve_uv(&mut hit, material, ray_direction, texture_manager, time);
+    if !is_cut_out(&hit, material, texture_manager) {
+        return hit;
+    }
+
+    let inner_origin = hit.point + *ray_direction * 1e-4;
+    let mut back = object.ray_intersect(&inner_origin, ray_direction);
+    if !back.is_intersecting {
+        return Intersect::empty();
+    }
+    back.normal = -back.normal; // vemos la cara interior
+    resolve_uv(&mut back, material, ray_direction, texture_manager, time);
+    if is_cut_out(&back, material, texture_manager) {
+        return Intersect::empty();
+    }
+
+    back.distance += hit.distance + 1e-4;
+    back
+}
+
+// Parallax occlusion mapping: recorre el height map en capas desde la UV del impacto
+// siguiendo la dirección de vista (en espacio tangente) hasta chocar con el relieve
+fn parallax_uv(
+    intersect: &Intersect,
+    material: &Material,
+    view_direction: &Vector3,
+    texture_manager: &TextureManager,
+) -> Option<Vector2> {
+    let uv = intersect.uv?;
+    let height_map_id = material.height_map_id?;
+    texture_manager.sample(height_map_id, Some(uv), intersect.point)?;
+
+    // vista en espacio tangente; se limita z para no disparar el desplazamiento en ángulos rasantes
+    let view_t = Vector3::new(
+        view_direction.dot(intersect.tangent),
+        view_direction.dot(intersect.bitangent),
+        view_direction.dot(intersect.normal).max(0.05),
+    );
+
+    let steps = material.parallax_steps.max(1);
+    let layer_depth = 1.0 / steps as f32;
+    let delta_uv = Vector2::new(view_t.x, view_t.y) * (material.parallax_scale / view_t.z / steps as f32);
+    let depth_at = |uv: Vector2| {
+        let texel = texture_manager.sample(height_map_id, Some(uv), intersect.point).unwrap_or(Color::WHITE);
+        1.0 - texel.r as f32 / 255.0
+    };
+
+    let mut current_uv = uv;
+    let mut current_layer = 0.0;
+    let mut current_depth = depth_at(current_uv);
+    for _ in 0..steps {
+        if current_layer >= current_depth {
+            break;
+        }
+        current_uv = current_uv - delta_uv;
+        current_depth = depth_at(current_uv);
+        current_layer += layer_depth;
+    }
+
+    // interpolamos entre la última capa por encima y la primera por debajo del relieve
+    let previous_uv = current_uv + delta_uv;
+    let after = current_depth - current_layer;
+    let before = depth_at(previous_uv) - (current_layer - layer_depth);
+    let weight = if (after - before).abs() > 1e-6 { after / (after - before) } else { 0.0 };
+    let final_uv = previous_uv * weight + current_uv * (1.0 - weight);
+
+    // sin recortar a [0, 1]: `get_color` repite la textura igual que con UV escaladas o animadas
+    Some(final_uv)
+}
+
+// Normal de sombreado: la normal geométrica inclinada por el normal map del material,
+// usando el marco tangente (u, v, n) de la cara
+fn shading_normal(intersect: &Intersect, material: &Material, texture_manager: &TextureManager, time: f32) -> Vector3 {
+    let mut normal = intersect.normal;
+
+    if let Some(normal_map_id) = material.normal_map_id {
+        if let Some(texel) = texture_manager.sample(normal_map_id, intersect.uv, intersect.point) {
+            let texel = color_to_vector3(texel);
+            let strength = material.normal_strength;
+            let tangent_normal = Vector3::new(
+                (texel.x * 2.0 - 1.0) * strength,
+                (texel.y * 2.0 - 1.0) * strength,
+                texel.z * 2.0 - 1.0,
+            );
+            normal = (intersect.tangent * tangent_normal.x
+                + intersect.bitangent * tangent_normal.y
+                + intersect.normal * tangent_normal.z)
+                .normalized();
+        }
+    }
+
+    // el agua ondula la normal (con o sin normal map debajo)
+    if let Some(waves) = material.waves {
+        normal = waves.perturb_normal(intersect.point, normal, time);
+    }
+    normal
+}
+
+// Promedia `samples` rayos repartidos en el lóbulo de rugosidad alrededor de `ideal`
+#[allow(clippy::too_many_arguments)]
+fn trace_lobe<T: RayIntersect>(
+    intersect: &Intersect,
+    ideal: &Vector3,
+    normal: &Vector3,
+    roughness: f32,
+    samples: u32,
+    rng: &mut Rng,
+    objects: &[T],
+    materials: &[Material],
+    lights: &[Light],
+    texture_manager: &TextureManager,
+    background: Color,
+    time: f32,
+    depth: u32,
+) -> Vector3 {
+    let mut sum = Vector3::zero();
+    for _ in 0..samples {
+        let direction = glossy_direction(ideal, normal, roughness, rng);
+        let origin = offset_origin(intersect, &direction);
+        sum += color_to_vector3(cast_ray(&origin, &direction, objects, materials, lights, texture_manager, background, time, depth + 1));
+    }
+    sum / samples as f32
+}
+
+fn cast_shadow<T: RayIntersect>(
+    intersect: &Intersect,
+    light: &Light,
+    objects: &[T],
+    materials: &[Material],
+    texture_manager: &TextureManager,
+    time: f32,
+) -> f32 {
+    let to_light = light.position - intersect.point;
+    let light_distance = to_light.length();
+    let light_direction = to_light / light_distance;
+    // del lado de la cara que mira a la luz (o por detrás, para la translucidez)
+    let shadow_ray_origin = offset_origin(intersect, &light_direction);
+
+    for (index, object) in objects.iter().enumerate() {
+        // el emisor que contiene la luz no la tapa; los demás sí hacen sombra
+        if light.source == Some(index) {
+            continue;
+        }
+        let shadow_intersect = intersect_with_cutout(object, &shadow_ray_origin, &light_direction, materials, texture_manager, time);
+        // Ignora la salida de un bloque en el que el rayo ya empezaba y lo que está detrás de la luz
+        if shadow_intersect.is_intersecting
+            && shadow_intersect.normal.dot(light_direction) <= 0.0
+            && shadow_intersect.distance < light_distance
+        {
+            return 0.8; //cambiar esto a una proporcion de la distancia para que haga el sh
+        }
+    }
+    0.0
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn cast_ray<T: RayIntersect>(
+    ray_origin: &Vector3,
+    ray_direction: &Vector3,
+    objects: &[T],
+    materials: &[Material],           // tabla compartida; los impactos guardan solo el índice
+    lights: &[Light],
+    texture_manager: &TextureManager, // <-- Pasa el texture manager
+    background: Color,                // color del cielo
+    time: f32,                        // segundos del reloj global (UV animadas)
+    depth: u32,                       // rebotes de reflexión/refracción ya hechos
+) -> Color {
+    let mut intersect = Intersect::empty();
+    let mut zbuffer = f32::INFINITY;
+
+    for object in objects {
+        let tmp = intersect_with_cutout(object, ray_origin, ray_direction, materials, texture_manager, time);
+        if tmp.is_intersecting {
+            if tmp.distance < zbuffer {
+                zbuffer = tmp.distance;
+                intersect = tmp;
+            }
+        }
+    }
+
+    if !intersect.is_intersecting {
+        return background;
+    }
+    let material = &materials[intersect.material_id as usize];
+    if material.alpha_cutoff <= 0.0 {
+        resolve_uv(&mut intersect, material, ray_direction, texture_manager, time);
+    }
+
+    let view_direction = (*ray_origin - intersect.point).normalized();
+
+    // --- NUEVA LÓGICA DE TEXTURA ---
+    let mut diffuse_color = material.diffuse;
+    if let Some(texture_id) = material.texture_id {
+        if let Some(texel) = texture_manager.sample(texture_id, intersect.uv, intersect.point) {
+            let texel = color_to_vector3(texel);
+            diffuse_color = material.texture_blend.apply(texel, diffuse_color);
+        }
+    }
+    // ----------------------------
+
+    let normal = shading_normal(&intersect, material, texture_manager, time);
+
+    // Ambient
+    let ambient_intensity = 0.2;
+    let ambient = diffuse_color * ambient_intensity; // <-- Usa diffuse_color
+
+    let mut color = ambient;
+    for light in lights {
+        let light_direction = (light.position - intersect.point).normalized();
+        let reflection_direction = reflect(&-light_direction, &normal).normalized();
+
+        let shadow_intensity = cast_shadow(&intersect, light, objects, materials, texture_manager, time);
+        let light_intensity = light.intensity_at(intersect.point) * (1.0 - shadow_intensity);
+
+        // Difuso y especular solo del lado iluminado; por detrás llega la translucidez
+        let n_dot_l = normal.dot(light_direction);
+        if n_dot_l > 0.0 {
+            color += match material.model {
+                ShadingModel::Phong => {
+                    // Difuso
+                    let diffuse_intensity = n_dot_l * light_intensity;
+                    let diffuse = diffuse_color * diffuse_intensity; // <-- Usa diffuse_color
+
+                    // Especular
+                    let specular_intensity = view_direction.dot(reflection_direction).max(0.0).powf(material.specular) * light_intensity;
+                    let specular = light.color * specular_intensity;
+
+                    diffuse * material.albedo[0] + specular * material.albedo[1]
+                }
+                ShadingModel::Pbr => pbr::direct_lighting(
+                    normal,
+                    view_direction,
+                    light_direction,
+                    light.color * light_intensity,
+                    diffuse_color,
+                    material.metallic,
+                    material.roughness,
+                ),
+            };
+        }
+
+        // Translucidez: la luz que llega por detrás de una cara delgada sale por
+        // delante teñida del color del material, más fuerte mirando hacia la luz
+        let translucency = material.translucency;
+        if translucency > 0.0 {
+            let back_light = (-n_dot_l).max(0.0);
+            let forward_scatter = (-view_direction.dot(light_direction)).max(0.0).powi(4);
+            let transmitted = translucency * light_intensity * (back_light * 0.6 + forward_scatter * 0.4);
+            color += diffuse_color * light.color * transmitted;
+        }
+    }
+
+    // Reflexión y refracción con la normal de sombreado, solo en los materiales que lo piden
+    let reflectivity = material.reflectivity;
+    let transparency = material.transparency;
+    if depth < MAX_RAY_DEPTH && material.traces_secondary_rays() && (reflectivity > 0.0 || transparency > 0.0) {
+        // solo el primer rebote se multiplica; los siguientes usan un único rayo jitterizado
+        let samples = if depth == 0 && material.roughness > 0.0 {
+            material.gloss_samples.max(1)
+        } else {
+            1
+        };
+        let mut rng = Rng::from_ray(ray_origin, ray_direction);
+
+        let reflect_direction = reflect(ray_direction, &normal).normalized();
+        let reflect_color = if reflectivity > 0.0 {
+            let reflected = trace_lobe(&intersect, &reflect_direction, &normal, material.roughness, samples, &mut rng, objects, materials, lights, texture_manager, background, time, depth);
+            match material.model {
+                ShadingModel::Phong => reflected,
+                // en Pbr el reflejo se tiñe con el Fresnel (los metales reflejan su color)
+                ShadingModel::Pbr => {
+                    let f0 = pbr::base_reflectance(diffuse_color, material.metallic);
+                    reflected * pbr::fresnel_schlick(normal.dot(view_direction).abs(), f0)
+                }
+            }
+        } else {
+            Vector3::zero()
+        };
+
+        let refract_color = if transparency > 0.0 {
+            // sin refracción posible (reflexión total interna) se refleja
+            let refract_direction = refract(ray_direction, &normal, material.ior)
+                .map(|direction| direction.normalized())
+                .unwrap_or(reflect_direction);
+            trace_lobe(&intersect, &refract_direction, &normal, material.roughness, samples, &mut rng, objects, materials, lights, texture_manager, background, time, depth)
+        } else {
+            Vector3::zero()
+        };
+
+        color = color * (1.0 - reflectivity - transparency)
+            + reflect_color * reflectivity
+            + refract_color * transparency;
+    }
+
+    // Emisión: se ve brillante a la cámara sin importar la luz
+    if material.is_emissive() {
+        let mut emission = material.emission * material.emission_strength;
+        if let Some(texture_id) = material.emission_texture_id {
+            if let Some(texel) = texture_manager.sample(texture_id, intersect.uv, intersect.point) {
+                emission = emission * color_to_vector3(texel);
+            }
+        }
+        color += emission;
+    }
+
+    vector3_to_color(color)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn render<T: RayIntersect>(
+    framebuffer: &mut Framebuffer, 
+    objects: &[T], 
+    materials: &[Material],
+    camera: &Camera, 
+    lights: &[Light],
+    texture_manager: &TextureManager, // <-- Pasa el texture manager
+    background: Color,                // color del cielo para los rayos que no chocan
+    time: f32,
+) {
+    let width = framebuffer.width as f32;
+    let height = framebuffer.height as f32;
+    let aspect_ratio = width / height;
+
+    for y in 0..framebuffer.height {
+        for x in 0..framebuffer.width {
+            let screen_x = (2.0 * x as f32) / width - 1.0;
+            let screen_y = -(2.0 * y as f32) / height + 1.0;
+
+            let (ray_origin, ray_direction) = camera.ray(screen_x, screen_y, aspect_ratio);
+
+            let pixel_color = cast_ray(&ray_origin, &ray_direction, objects, materials, lights, texture_manager, background, time, 0); // <-- Pasa el texture manager
+
+            framebuffer.set_current_color(pixel_color);
+            framebuffer.set_pixel(x, y);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::Cube;
+    use crate::texture::Texture;
+
+    // Textura 2x1: mitad izquierda transparente (u < 0.5), mitad derecha opaca
+    fn half_cut_scene(left_alpha: u8) -> (Cube, Vec<Material>, TextureManager) {
+        let mut texture_manager = TextureManager::new();
+        let texture_id = texture_manager.add_texture(Texture {
+            width: 2,
+            height: 1,
+            data: vec![Color::new(255, 255, 255, left_alpha), Color::new(255, 255, 255, 255)],
+        });
+        let material = Material::new(Vector3::one(), [1.0, 0.0], 1.0)
+            .with_texture(texture_id)
+            .with_alpha_cutout(0.5);
+        let cube = Cube { center: Vector3::zero(), size: 1.0, material_id: 0 };
+        (cube, vec![material], texture_manager)
+    }
+
+    #[test]
+    fn cutout_lets_rays_through_transparent_texels() {
+        let (cube, materials, textures) = half_cut_scene(0);
+        let direction = Vector3::new(0.0, 0.0, -1.0);
+
+        // cara +z en x = -0.25 -> u = 0.75, opaca: se ve la cara de entrada
+        let hit = intersect_with_cutout(&cube, &Vector3::new(-0.25, 0.0, 2.0), &direction, &materials, &textures, 0.0);
+        assert!(hit.is_intersecting);
+        assert!((hit.distance - 1.5).abs() < 1e-4);
+
+        // en x = 0.25 la entrada (u = 0.25) está recortada y la cara de atrás (u = 0.75) no
+        let hit = intersect_with_cutout(&cube, &Vector3::new(0.25, 0.0, 2.0), &direction, &materials, &textures, 0.0);
+        assert!(hit.is_intersecting);
+        assert!((hit.distance - 2.5).abs() < 1e-3);
+        assert_eq!(hit.normal, Vector3::new(0.0, 0.0, 1.0)); // cara interior, mirando al rayo
+    }
+
+    #[test]
+    fn cutout_misses_when_both_faces_are_transparent() {
+        let (cube, materials, textures) = half_cut_scene(0);
+        // rayo vertical: arriba y abajo u = x + 0.5, así que en x = -0.25 las dos caras están recortadas
+        let hit = intersect_with_cutout(
+            &cube,
+            &Vector3::new(-0.25, 2.0, 0.0),
+            &Vector3::new(0.0, -1.0, 0.0),
+            &materials,
+            &textures,
+            0.0,
+        );
+        assert!(!hit.is_intersecting);
+
+        // con alfa 200 en la mitad izquierda ya no hay recorte
+        let (cube, materials, textures) = half_cut_scene(200);
+        let hit = intersect_with_cutout(
+            &cube,
+            &Vector3::new(-0.25, 2.0, 0.0),
+            &Vector3::new(0.0, -1.0, 0.0),
+            &materials,
+            &textures,
+            0.0,
+        );
+        assert!(hit.is_intersecting);
+        assert!((hit.distance - 1.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn emitters_shadow_every_light_but_their_own() {
+        let glow = Material::new(Vector3::one(), [1.0, 0.0], 1.0).with_emission(Vector3::one(), 1.0);
+        let objects = vec![
+            Cube { center: Vector3::new(0.0, 3.0, 0.0), size: 1.0, material_id: 0 },
+            Cube { center: Vector3::new(0.0, 1.5, 0.0), size: 1.0, material_id: 0 },
+        ];
+        let materials = vec![glow];
+        let textures = TextureManager::new();
+        let floor = Intersect::new(0, 1.0, Vector3::new(0.0, 1.0, 0.0), Vector3::zero(), None);
+
+        // la luz del bloque de arriba queda tapada por el bloque emisivo de abajo
+        let upper = Light::from_emissive(objects[0].center, &materials[0], 0);
+        assert_eq!(cast_shadow(&floor, &upper, &objects, &materials, &textures, 0.0), 0.8);
+
+        // la del bloque de abajo sale de su propio cubo; el de arriba está detrás de la luz
+        let lower = Light::from_emissive(objects[1].center, &materials[0], 1);
+        assert_eq!(cast_shadow(&floor, &lower, &objects, &materials, &textures, 0.0), 0.0);
+    }
+
+    #[test]
+    fn rough_reflective_cube_picks_up_its_neighbour() {
+        // rayo a 45° contra la cara +x del espejo; el reflejo va hacia el cubo rojo emisivo
+        let objects = vec![
+            Cube { center: Vector3::zero(), size: 1.0, material_id: 0 },
+            Cube { center: Vector3::new(1.5, 0.0, -1.0), size: 1.0, material_id: 1 },
+        ];
+        let origin = Vector3::new(1.5, 0.0, 1.0);
+        let direction = Vector3::new(-1.0, 0.0, -1.0).normalized();
+        let red = Material::new(Vector3::zero(), [1.0, 0.0], 1.0).with_emission(Vector3::new(1.0, 0.0, 0.0), 1.0);
+        let shade = |mirror: Material| {
+            let materials = vec![mirror, red];
+            let textures = TextureManager::new();
+            cast_ray(&origin, &direction, &objects, &materials, &[], &textures, Color::BLACK, 0.0, 0)
+        };
+
+        let mirror = Material::new(Vector3::zero(), [1.0, 0.0], 1.0).with_optics(0.0, 0.8, 1.0);
+        let glossy = shade(mirror.with_gloss(0.05, 4));
+        assert!(glossy.r > 150 && glossy.g < 30, "{:?}", glossy);
+
+        // sin rugosidad (ni olas ni Pbr) un Phong se queda con la luz directa
+        let flat = shade(mirror);
+        assert_eq!((flat.r, flat.g, flat.b), (0, 0, 0));
+    }
+
+    #[test]
+    fn parallax_keeps_uvs_outside_the_unit_square() {
+        // height map negro: el relieve está al fondo y el rayo recorre todas las capas
+        let mut textures = TextureManager::new();
+        let height_map = textures.add_texture(Texture { width: 1, height: 1, data: vec![Color::BLACK] });
+        let material = Material::new(Vector3::one(), [1.0, 0.0], 1.0).with_parallax(height_map, 0.1, 8);
+        let mut hit = Intersect::new(0, 1.0, Vector3::new(0.0, 0.0, 1.0), Vector3::zero(), Some(Vector2::new(0.02, 0.5)));
+        hit.tangent = Vector3::new(1.0, 0.0, 0.0);
+        hit.bitangent = Vector3::new(0.0, 1.0, 0.0);
+
+        let view_direction = Vector3::new(1.0, 0.0, 1.0).normalized();
+        let uv = parallax_uv(&hit, &material, &view_direction, &textures).unwrap();
+        // en una textura repetida el desplazamiento cruza el borde en vez de pegarse a u = 0
+        assert!((uv.x + 0.08).abs() < 1e-4, "{:?}", uv);
+    }
+
+    #[test]
+    fn shipped_leaves_have_cutout_holes() {
+        // con una textura RGB sin alfa el recorte de [leaves] no haría nada
+        let mut textures = TextureManager::new();
+        let library = crate::material_library::MaterialLibrary::load("assets/materials.toml", &mut textures).unwrap();
+        let leaves = library.get("leaves").unwrap();
+        let texture = textures.get_texture(leaves.texture_id.unwrap()).unwrap();
+        let cutoff = (leaves.alpha_cutoff * 255.0) as u8;
+        assert!(texture.data.iter().any(|c| c.a < cutoff));
+        assert!(texture.data.iter().any(|c| c.a >= cutoff));
+    }
+}
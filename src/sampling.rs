@@ -0,0 +1,65 @@
+// sampling.rs
+use crate::math::Vector3;
+
+// Generador pseudoaleatorio pequeño (xorshift32). Se siembra con el rayo para que
+// el mismo píxel dé siempre el mismo ruido y la imagen no parpadee entre frames
+pub struct Rng {
+    state: u32,
+}
+
+impl Rng {
+    pub fn new(seed: u32) -> Self {
+        Rng { state: seed.max(1) }
+    }
+
+    pub fn from_ray(origin: &Vector3, direction: &Vector3) -> Self {
+        let mut hash: u32 = 2166136261; // FNV-1a sobre los bits de los componentes
+        for value in [origin.x, origin.y, origin.z, direction.x, direction.y, direction.z] {
+            hash ^= value.to_bits();
+            hash = hash.wrapping_mul(16777619);
+        }
+        Rng::new(hash)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    // Uniforme en [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    pub fn in_unit_sphere(&mut self) -> Vector3 {
+        loop {
+            let p = Vector3::new(
+                self.next_f32() * 2.0 - 1.0,
+                self.next_f32() * 2.0 - 1.0,
+                self.next_f32() * 2.0 - 1.0,
+            );
+            if p.dot(p) < 1.0 {
+                return p;
+            }
+        }
+    }
+}
+
+// Dirección dentro del lóbulo alrededor de `ideal`; `roughness` 0 = espejo, 1 = muy abierto.
+// Si la muestra cruza al otro lado de la superficie se usa la dirección ideal
+pub fn glossy_direction(ideal: &Vector3, normal: &Vector3, roughness: f32, rng: &mut Rng) -> Vector3 {
+    if roughness <= 0.0 {
+        return *ideal;
+    }
+
+    let sample = (*ideal + rng.in_unit_sphere() * roughness).normalized();
+    if sample.dot(*normal).signum() == ideal.dot(*normal).signum() {
+        sample
+    } else {
+        *ideal
+    }
+}
@@ -0,0 +1,141 @@
+// scene.rs
+use crate::math::Color;
+use std::collections::HashMap;
+
+use crate::camera::Camera;
+use crate::framebuffer::Framebuffer;
+use crate::light::Light;
+use crate::material_library::MaterialLibrary;
+use crate::primitive::Primitive;
+use crate::render::render;
+use crate::texture::TextureManager;
+
+// Todo lo que necesita el render: geometría, tabla de materiales, luces, texturas y cámara
+pub struct Scene {
+    pub objects: Vec<Primitive>,
+    pub materials: MaterialLibrary,
+    pub lights: Vec<Light>,
+    pub textures: TextureManager,
+    pub camera: Camera,
+    pub background: Color,           // color del cielo
+}
+
+// Los emisores se agrupan en celdas de este lado (en unidades del mundo): cada luz cuesta un
+// rayo de sombra contra todos los objetos por punto sombreado, así que un lago de lava no
+// puede convertirse en cientos de luces
+pub const EMISSIVE_CELL_SIZE: f32 = 4.0;
+// Tope de luces emisivas; si hay más celdas se quedan las más intensas
+pub const MAX_EMISSIVE_LIGHTS: usize = 32;
+
+impl Scene {
+    // Los bloques emisivos iluminan la escena como luces puntuales, una por celda de
+    // EMISSIVE_CELL_SIZE: la luz queda dentro del emisor más intenso de la celda, con la
+    // intensidad sumada de todos y su color promedio
+    pub fn add_emissive_lights(&mut self) {
+        let materials = self.materials.materials();
+        let mut cells: HashMap<(i64, i64, i64), Light> = HashMap::new();
+        let mut brightest: HashMap<(i64, i64, i64), f32> = HashMap::new();
+        for (index, object) in self.objects.iter().enumerate() {
+            let material = &materials[object.material_id() as usize];
+            if !material.is_emissive() {
+                continue;
+            }
+            let center = object.center();
+            let cell = |v: f32| (v / EMISSIVE_CELL_SIZE).floor() as i64;
+            let key = (cell(center.x), cell(center.y), cell(center.z));
+
+            let emitter = Light::from_emissive(center, material, index);
+            let Some(light) = cells.get_mut(&key) else {
+                brightest.insert(key, emitter.intensity);
+                cells.insert(key, emitter);
+                continue;
+            };
+            // color promedio pesado por intensidad
+            let total = light.intensity + emitter.intensity;
+            light.color = (light.color * light.intensity + emitter.color * emitter.intensity) / total;
+            light.intensity = total;
+            if emitter.intensity > brightest[&key] {
+                brightest.insert(key, emitter.intensity);
+                light.position = emitter.position;
+                light.source = emitter.source;
+            }
+        }
+
+        // orden fijo (más intensas primero, luego por objeto) para que el render no dependa del HashMap
+        let mut lights: Vec<Light> = cells.into_values().collect();
+        lights.sort_by(|a, b| b.intensity.total_cmp(&a.intensity).then(a.source.cmp(&b.source)));
+        lights.truncate(MAX_EMISSIVE_LIGHTS);
+        self.lights.extend(lights);
+    }
+
+    pub fn render(&self, framebuffer: &mut Framebuffer, time: f32) {
+        render(
+            framebuffer,
+            &self.objects,
+            self.materials.materials(),
+            &self.camera,
+            &self.lights,
+            &self.textures,
+            self.background,
+            time,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::Cube;
+    use crate::material::Material;
+    use crate::math::Vector3;
+
+    fn emitters(cubes: &[(Vector3, f32)]) -> Scene {
+        let mut materials = MaterialLibrary::new();
+        let mut objects = Vec::new();
+        for (i, (center, strength)) in cubes.iter().enumerate() {
+            let material = Material::new(Vector3::zero(), [1.0, 0.0], 1.0).with_emission(Vector3::new(1.0, i as f32 % 2.0, 0.0), *strength);
+            let material_id = materials.insert(&format!("lava_{}", i), material).unwrap();
+            objects.push(Cube { center: *center, size: 1.0, material_id }.into());
+        }
+        Scene {
+            objects,
+            materials,
+            lights: Vec::new(),
+            textures: TextureManager::new(),
+            camera: Camera::new(Vector3::new(0.0, 0.0, 5.0), Vector3::zero(), Vector3::new(0.0, 1.0, 0.0)),
+            background: Color::BLACK,
+        }
+    }
+
+    #[test]
+    fn neighbouring_emitters_share_one_light() {
+        let mut scene = emitters(&[(Vector3::new(0.5, 0.5, 0.5), 1.0), (Vector3::new(1.5, 0.5, 0.5), 3.0)]);
+        scene.add_emissive_lights();
+        assert_eq!(scene.lights.len(), 1);
+
+        // dentro del emisor más intenso, con la intensidad de ambos y el color pesado
+        let light = &scene.lights[0];
+        assert_eq!(light.source, Some(1));
+        assert_eq!(light.position, Vector3::new(1.5, 0.5, 0.5));
+        assert_eq!(light.intensity, 4.0);
+        assert_eq!(light.color, Vector3::new(1.0, 0.75, 0.0));
+    }
+
+    #[test]
+    fn distant_emitters_keep_their_own_light() {
+        let mut scene = emitters(&[(Vector3::new(0.5, 0.5, 0.5), 1.0), (Vector3::new(10.5, 0.5, 0.5), 2.0)]);
+        scene.add_emissive_lights();
+        let sources: Vec<Option<usize>> = scene.lights.iter().map(|light| light.source).collect();
+        assert_eq!(sources, vec![Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn emissive_lights_are_capped() {
+        let cubes: Vec<(Vector3, f32)> = (0..100).map(|i| (Vector3::new(i as f32 * 8.0, 0.5, 0.5), 1.0 + i as f32)).collect();
+        let mut scene = emitters(&cubes);
+        scene.add_emissive_lights();
+        assert_eq!(scene.lights.len(), MAX_EMISSIVE_LIGHTS);
+        // se quedan las más intensas
+        assert!(scene.lights.iter().all(|light| light.source.unwrap() >= 100 - MAX_EMISSIVE_LIGHTS));
+    }
+}
@@ -0,0 +1,434 @@
+// scene_file.rs
+// Escena completa descrita en TOML, para cambiar de escena sin recompilar:
+//
+//     background = [0.8, 0.72, 0.8]      # cielo (opcional)
+//     materials = "../materials.toml"    # biblioteca externa, o tablas [materials.nombre]
+//
+//     [camera]
+//     eye = [1.5, 0.6, 12.0]
+//     center = [-0.5, 1.1, 1.0]
+//     orbit = [-0.3, 0.2]                # giro (yaw, pitch) aplicado al cargar (opcional)
+//     fov = 60.0                         # grados (opcional)
+//     # projection = "isometric"         # u "orthographic"; con view_height = alto de la vista
+//
+//     [[lights]]
+//     position = [0.0, 2.0, 4.0]
+//     intensity = 0.5
+//
+//     [textures]
+//     bark = "wood.png"                  # imagen, relativa al archivo de la escena
+//     veins = { pattern = "marble", frequency = 3.0, space = "world" }
+//
+//     [prefabs]
+//     tree = "../prefabs/tree.toml"      # estructuras por nombre (ver prefab.rs)
+//
+//     [[maps]]
+//     file = "../maps/diorama.toml"      # mapa de voxeles en texto (ver voxel_map.rs)
+//     offset = [0.0, 0.0, 0.0]           # opcional
+//
+//     [[terrain]]
+//     seed = 7                           # paisaje generado (ver terrain.rs)
+//     size = [32, 32]
+//     trees = ["tree"]
+//
+//     [[vox]]
+//     file = "../vox/house.vox"          # modelo de MagicaVoxel (ver vox.rs) o mundo .rvx (voxel_file.rs)
+//     offset = [0.0, 0.0, 0.0]           # opcional
+//     scale = 0.25                       # lado de cada voxel (1 por defecto)
+//     overrides = { 12 = { transparency = 0.6 }, 200 = { material = "water" } }
+//
+//     [[placements]]
+//     prefab = "tree"
+//     position = [3.0, 0.0, 1.0]         # donde cae el ancla del prefab
+//     rotation = 90                      # grados, múltiplo de 90 (opcional)
+//     mirror = "x"                       # "x", "z" o "xz" (opcional)
+//     scale = 1.0                        # opcional
+//
+//     [[primitives]]
+//     type = "cube"
+//     center = [0.0, -0.5, 0.0]
+//     size = 1.0
+//     material = "grass"
+//
+// Los materiales pueden usar el nombre de una textura de [textures] en lugar de una ruta.
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::camera::{Camera, Projection};
+use crate::cube::Cube;
+use crate::light::Light;
+use crate::material::MaterialId;
+use crate::material_library::{MaterialDef, MaterialLibrary, check_range};
+use crate::math::{Color, Vector3};
+use crate::prefab::{Placement, Prefab};
+use crate::primitive::Primitive;
+use crate::procedural::ProceduralTexture;
+use crate::render::SKYBOX_COLOR;
+use crate::scene::Scene;
+use crate::sphere::Sphere;
+use crate::terrain::{Terrain, TerrainDef};
+use crate::texture::{TextureDef, TextureManager};
+use crate::vox::load_vox;
+use crate::voxel_file::VoxelWorld;
+use crate::voxel_grid::PaletteOverride;
+use crate::voxel_map::VoxelMap;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SceneDef {
+    pub background: Option<[f32; 3]>,
+    pub camera: CameraDef,
+    #[serde(default)]
+    pub lights: Vec<LightDef>,
+    #[serde(default)]
+    pub textures: HashMap<String, TextureDef>,
+    pub materials: Option<MaterialsDef>,
+    #[serde(default)]
+    pub prefabs: HashMap<String, String>,
+    #[serde(default)]
+    pub maps: Vec<MapRefDef>,
+    #[serde(default)]
+    pub terrain: Vec<TerrainDef>,
+    #[serde(default)]
+    pub vox: Vec<VoxRefDef>,
+    #[serde(default)]
+    pub placements: Vec<PlacementDef>,
+    #[serde(default)]
+    pub primitives: Vec<PrimitiveDef>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MapRefDef {
+    pub file: String,
+    pub offset: Option<[f32; 3]>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VoxRefDef {
+    pub file: String,
+    pub offset: Option<[f32; 3]>,
+    pub scale: Option<f32>,
+    #[serde(default)]
+    pub overrides: HashMap<String, PaletteOverrideDef>, // clave: índice de paleta 1..255
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PaletteOverrideDef {
+    pub material: Option<String>,
+    pub transparency: Option<f32>,
+    pub reflectivity: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlacementDef {
+    pub prefab: String,
+    pub position: [f32; 3],
+    pub rotation: Option<i32>,
+    pub mirror: Option<String>,
+    pub scale: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CameraDef {
+    pub eye: [f32; 3],
+    pub center: [f32; 3],
+    pub up: Option<[f32; 3]>,
+    pub orbit: Option<[f32; 2]>,
+    pub projection: Option<String>, // "perspective" (por defecto), "orthographic" o "isometric"
+    pub fov: Option<f32>,           // grados, solo en perspectiva
+    pub view_height: Option<f32>,   // alto de la vista ortográfica en unidades del mundo
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LightDef {
+    pub position: [f32; 3],
+    pub color: Option<[f32; 3]>,
+    pub intensity: f32,
+    pub attenuation: Option<f32>,
+}
+
+// Ruta a una biblioteca de materiales o las definiciones en la misma escena
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum MaterialsDef {
+    File(String),
+    Inline(HashMap<String, MaterialDef>),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum PrimitiveDef {
+    Cube { center: [f32; 3], size: f32, material: String },
+    Sphere { center: [f32; 3], radius: f32, material: String },
+}
+
+impl Scene {
+    pub fn load(file_path: &str) -> Result<Scene, String> {
+        let source = std::fs::read_to_string(file_path)
+            .map_err(|e| format!("{}: {}", file_path, e))?;
+        let base_dir = Path::new(file_path).parent().unwrap_or(Path::new(""));
+        Self::from_toml_str(&source, base_dir).map_err(|e| format!("{}: {}", file_path, e))
+    }
+
+    // `base_dir` es la carpeta contra la que se resuelven las rutas del archivo
+    pub fn from_toml_str(source: &str, base_dir: &Path) -> Result<Scene, String> {
+        let def: SceneDef = toml::from_str(source).map_err(|e| e.to_string())?;
+        Self::from_def(&def, base_dir)
+    }
+
+    pub fn from_def(def: &SceneDef, base_dir: &Path) -> Result<Scene, String> {
+        let mut textures = TextureManager::new();
+
+        let mut names: Vec<&String> = def.textures.keys().collect();
+        names.sort(); // ids deterministas
+        let mut named_textures = HashMap::new();
+        for name in names {
+            let id = load_texture(&def.textures[name], base_dir, &mut textures)
+                .map_err(|e| format!("texture '{}': {}", name, e))?;
+            named_textures.insert(name.clone(), id);
+        }
+
+        let mut materials = match &def.materials {
+            None => MaterialLibrary::new(),
+            Some(MaterialsDef::Inline(defs)) => {
+                MaterialLibrary::from_defs_with_textures(defs, base_dir, &mut textures, &named_textures)?
+            }
+            Some(MaterialsDef::File(path)) => {
+                let full_path = base_dir.join(path);
+                let source = std::fs::read_to_string(&full_path)
+                    .map_err(|e| format!("materials \"{}\": {}", full_path.display(), e))?;
+                let library_dir = full_path.parent().unwrap_or(Path::new(""));
+                toml::from_str::<HashMap<String, MaterialDef>>(&source)
+                    .map_err(|e| e.to_string())
+                    .and_then(|defs| {
+                        MaterialLibrary::from_defs_with_textures(&defs, library_dir, &mut textures, &named_textures)
+                    })
+                    .map_err(|e| format!("{}: {}", full_path.display(), e))?
+            }
+        };
+
+        let camera_def = &def.camera;
+        let mut camera = Camera::new(
+            vector3(camera_def.eye),
+            vector3(camera_def.center),
+            vector3(camera_def.up.unwrap_or([0.0, 1.0, 0.0])),
+        );
+        // el giro va antes de la proyección: la isométrica ajusta el ojo a la diagonal
+        if let Some([yaw, pitch]) = camera_def.orbit {
+            camera.orbit(yaw, pitch);
+        }
+        let field_err = |field: &str, message: String| format!("camera: field '{}' {}", field, message);
+        let projection = camera_def.projection.as_deref().unwrap_or("perspective");
+        if let Some(fov) = camera_def.fov {
+            if projection != "perspective" {
+                return Err(field_err("fov", "only applies to the perspective projection".to_string()));
+            }
+            if !(fov > 0.0 && fov < 180.0) {
+                return Err(field_err("fov", format!("must be between 0 and 180 degrees (got {})", fov)));
+            }
+            camera = camera.with_projection(Projection::Perspective { fov: fov.to_radians() });
+        }
+        // sin view_height la vista ortográfica encuadra lo mismo que la perspectiva por defecto
+        let view_height = camera_def.view_height.unwrap_or_else(|| camera.view_height_at_center());
+        if !(view_height > 0.0 && view_height.is_finite()) {
+            return Err(field_err("view_height", format!("must be positive (got {})", view_height)));
+        }
+        camera = match projection {
+            "perspective" if camera_def.view_height.is_some() => {
+                return Err(field_err("view_height", "only applies to orthographic projections".to_string()));
+            }
+            "perspective" => camera,
+            "orthographic" => camera.with_projection(Projection::Orthographic { height: view_height }),
+            "isometric" => camera.with_isometric(view_height),
+            other => {
+                return Err(field_err(
+                    "projection",
+                    format!("must be \"perspective\", \"orthographic\" or \"isometric\" (got \"{}\")", other),
+                ));
+            }
+        };
+
+        let mut lights = Vec::with_capacity(def.lights.len());
+        for (i, light_def) in def.lights.iter().enumerate() {
+            let field_err = |field: &str, message: String| format!("light {}: field '{}' {}", i, field, message);
+            check_range(light_def.intensity, 0.0, f32::MAX).map_err(|m| field_err("intensity", m))?;
+            let mut light = Light::new(
+                vector3(light_def.position),
+                vector3(light_def.color.unwrap_or([1.0, 1.0, 1.0])),
+                light_def.intensity,
+            );
+            if let Some(attenuation) = light_def.attenuation {
+                check_range(attenuation, 0.0, f32::MAX).map_err(|m| field_err("attenuation", m))?;
+                light.attenuation = attenuation;
+            }
+            lights.push(light);
+        }
+
+        let mut names: Vec<&String> = def.prefabs.keys().collect();
+        names.sort(); // errores deterministas
+        let mut prefabs = HashMap::new();
+        for name in names {
+            let full_path = base_dir.join(&def.prefabs[name]);
+            prefabs.insert(name.clone(), Prefab::load(&full_path.to_string_lossy())?);
+        }
+
+        let mut objects = Vec::new();
+        for map_ref in &def.maps {
+            let full_path = base_dir.join(&map_ref.file);
+            let map = VoxelMap::load(&full_path.to_string_lossy())?;
+            let offset = vector3(map_ref.offset.unwrap_or([0.0, 0.0, 0.0]));
+            objects.extend(
+                map.build(&materials, &prefabs, offset)
+                    .map_err(|e| format!("{}: {}", full_path.display(), e))?,
+            );
+        }
+
+        for (i, terrain_def) in def.terrain.iter().enumerate() {
+            let context = |e: String| format!("terrain {}: {}", i, e);
+            let terrain = Terrain::from_def(terrain_def).map_err(context)?;
+            let offset = vector3(terrain_def.offset.unwrap_or([0.0, 0.0, 0.0]));
+            objects.extend(terrain.build(&materials, &prefabs, offset).map_err(context)?);
+        }
+
+        for vox_ref in &def.vox {
+            let full_path = base_dir.join(&vox_ref.file);
+            let context = |e: String| format!("{}: {}", full_path.display(), e);
+            // los .rvx traen sus propios overrides; los de la escena los reemplazan
+            let VoxelWorld { grid, mut overrides } = if full_path.extension().is_some_and(|e| e == "rvx") {
+                VoxelWorld::load(&full_path.to_string_lossy())?
+            } else {
+                VoxelWorld::new(load_vox(&full_path.to_string_lossy())?)
+            };
+            for (key, override_def) in &vox_ref.overrides {
+                let index = match key.parse::<u8>() {
+                    Ok(index) if index > 0 => index,
+                    _ => return Err(context(format!("override key \"{}\" must be a palette index from 1 to 255", key))),
+                };
+                overrides.insert(index, PaletteOverride {
+                    material: override_def.material.clone(),
+                    transparency: override_def.transparency,
+                    reflectivity: override_def.reflectivity,
+                });
+            }
+
+            let scale = vox_ref.scale.unwrap_or(1.0);
+            if !(scale > 0.0 && scale.is_finite()) {
+                return Err(context(format!("field 'scale' must be positive (got {})", scale)));
+            }
+            let name = Path::new(&vox_ref.file)
+                .file_stem()
+                .map_or_else(|| vox_ref.file.clone(), |stem| stem.to_string_lossy().into_owned());
+            let offset = vector3(vox_ref.offset.unwrap_or([0.0, 0.0, 0.0]));
+            objects.extend(grid.build(&name, &mut materials, &overrides, scale, offset).map_err(context)?);
+        }
+
+        for (i, placement_def) in def.placements.iter().enumerate() {
+            let context = |e: String| format!("placement {}: {}", i, e);
+            let prefab = prefabs
+                .get(&placement_def.prefab)
+                .ok_or_else(|| context(format!("unknown prefab '{}'", placement_def.prefab)))?;
+            let scale = placement_def.scale.unwrap_or(1.0);
+            if !(scale > 0.0 && scale.is_finite()) {
+                return Err(context(format!("field 'scale' must be positive (got {})", scale)));
+            }
+            let mut placement = Placement::at(vector3(placement_def.position))
+                .with_scale(scale)
+                .with_rotation(placement_def.rotation.unwrap_or(0))
+                .map_err(context)?;
+            if let Some(mirror) = &placement_def.mirror {
+                placement = placement.with_mirror(mirror).map_err(context)?;
+            }
+            prefab
+                .place(&placement, &materials, &mut objects)
+                .map_err(|e| context(format!("prefab '{}': {}", placement_def.prefab, e)))?;
+        }
+
+        for (i, primitive_def) in def.primitives.iter().enumerate() {
+            let field_err = |field: &str, message: String| format!("primitive {}: field '{}' {}", i, field, message);
+            let material_id = |name: &str| -> Result<MaterialId, String> {
+                materials
+                    .id(name)
+                    .ok_or_else(|| field_err("material", format!("names unknown material '{}'", name)))
+            };
+            let primitive: Primitive = match primitive_def {
+                PrimitiveDef::Cube { center, size, material } => {
+                    if !(*size > 0.0 && size.is_finite()) {
+                        return Err(field_err("size", format!("must be a positive finite number (got {})", size)));
+                    }
+                    Cube { center: vector3(*center), size: *size, material_id: material_id(material)? }.into()
+                }
+                PrimitiveDef::Sphere { center, radius, material } => {
+                    if !(*radius > 0.0 && radius.is_finite()) {
+                        return Err(field_err("radius", format!("must be a positive finite number (got {})", radius)));
+                    }
+                    Sphere { center: vector3(*center), radius: *radius, material_id: material_id(material)? }.into()
+                }
+            };
+            objects.push(primitive);
+        }
+
+        let background = match def.background {
+            Some(c) => {
+                for (i, v) in c.iter().enumerate() {
+                    check_range(*v, 0.0, 1.0).map_err(|m| format!("field 'background[{}]' {}", i, m))?;
+                }
+                Color::new(to_u8(c[0]), to_u8(c[1]), to_u8(c[2]), 255)
+            }
+            None => SKYBOX_COLOR,
+        };
+
+        let mut scene = Scene { objects, materials, lights, textures, camera, background };
+        scene.add_emissive_lights();
+        Ok(scene)
+    }
+}
+
+fn load_texture(def: &TextureDef, base_dir: &Path, textures: &mut TextureManager) -> Result<usize, String> {
+    match def {
+        TextureDef::Image(path) => {
+            let full_path = base_dir.join(path);
+            textures
+                .load_texture(&full_path.to_string_lossy())
+                .map_err(|e| format!("could not load \"{}\": {}", full_path.display(), e))
+        }
+        TextureDef::Procedural(procedural) => Ok(textures.add_procedural(ProceduralTexture::from_def(procedural)?)),
+    }
+}
+
+fn vector3(v: [f32; 3]) -> Vector3 {
+    Vector3::new(v[0], v[1], v[2])
+}
+
+fn to_u8(v: f32) -> u8 {
+    (v * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diorama_scene_matches_the_original_hard_coded_one() {
+        let scene = Scene::load("assets/scenes/diorama.toml").unwrap();
+
+        // 69 bloques de suelo (G y T llevan dos) + 2 árboles chicos de 8 cubos y 2 altos de 19
+        assert_eq!(scene.objects.len(), 69 + 2 * 8 + 2 * 19);
+
+        let mut camera = Camera::new(Vector3::new(1.5, 0.6, 12.0), Vector3::new(-0.5, 1.1, 1.0), Vector3::new(0.0, 6.0, 0.0));
+        camera.orbit(-0.3, 0.2);
+        assert!((scene.camera.eye - camera.eye).length() < 1e-5);
+        assert!((scene.camera.forward - camera.forward).length() < 1e-5);
+
+        assert_eq!(scene.lights.len(), 1);
+        let light = &scene.lights[0];
+        assert_eq!((light.position, light.intensity), (Vector3::new(0.0, 2.0, 4.0), 0.5));
+        assert_eq!(scene.background, SKYBOX_COLOR);
+    }
+}
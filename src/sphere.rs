@@ -1,11 +1,13 @@
-use crate::material::Material;
+// sphere.rs
+use crate::material::MaterialId;
 use crate::ray_intersect::{Intersect, RayIntersect};
-use raylib::prelude::Vector3;
+use crate::math::Vector3;
 
+#[derive(Debug, Clone, Copy)]
 pub struct Sphere {
     pub center: Vector3,
     pub radius: f32,
-    pub material: Material,
+    pub material_id: MaterialId,
 }
 
 impl RayIntersect for Sphere {
@@ -25,7 +27,7 @@ impl RayIntersect for Sphere {
                 let normal = (point - self.center).normalized();
                 let distance = t;
 
-                return Intersect::new(point, normal, distance, self.material);
+                return Intersect::new(self.material_id, distance, normal, point, None);
             }
         }
 
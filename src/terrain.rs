@@ -0,0 +1,477 @@
+// terrain.rs
+// Paisajes de voxeles generados a partir de una semilla: relieve con ruido fbm, ríos y
+// estanques de agua, árboles (prefabs de la escena) repartidos al azar y el césped en dos
+// tonos. La misma semilla con los mismos parámetros da siempre el mismo mundo.
+//
+//     [[terrain]]
+//     seed = 7
+//     size = [32, 32]                   # columnas (x) y filas (z)
+//     height = 5                        # altura máxima en bloques sobre el nivel 0
+//     water_level = 1                   # el agua llena las columnas más bajas hasta este nivel
+//     hill_size = 12.0                  # ancho típico de las colinas, en bloques
+//     rivers = 1                        # ríos que cruzan el mapa
+//     river_width = 1.5                 # en bloques
+//     ponds = 2                         # estanques redondos además de los huecos del relieve
+//     trees = ["tree", "tall_tree"]     # prefabs cargados por la escena
+//     tree_density = 0.05               # probabilidad de árbol en cada columna de césped
+//     materials = { grass = "grass", grass_dark = "grass_dark", water = "water", ground = "grass" }
+//     offset = [0.0, 0.0, 0.0]
+//
+// Todo es opcional salvo `seed` y `size`. El mapa queda centrado en el origen con la cara
+// superior del nivel 0 en y = 0, igual que los mapas de voxel_map.rs.
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::cube::Cube;
+use crate::material::MaterialId;
+use crate::material_library::{MaterialLibrary, check_range};
+use crate::math::Vector3;
+use crate::prefab::{Placement, Prefab};
+use crate::primitive::Primitive;
+use crate::voxel_grid::check_size;
+use crate::procedural::Perlin;
+use crate::sampling::Rng;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TerrainDef {
+    pub seed: u64,
+    pub size: [usize; 2],
+    pub tile: Option<f32>,
+    pub height: Option<u32>,
+    pub water_level: Option<u32>,
+    pub hill_size: Option<f32>,
+    pub rivers: Option<u32>,
+    pub river_width: Option<f32>,
+    pub ponds: Option<u32>,
+    #[serde(default)]
+    pub trees: Vec<String>,
+    pub tree_density: Option<f32>,
+    #[serde(default)]
+    pub materials: TerrainMaterialsDef,
+    pub offset: Option<[f32; 3]>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TerrainMaterialsDef {
+    pub grass: Option<String>,
+    pub grass_dark: Option<String>,
+    pub water: Option<String>,
+    pub ground: Option<String>,
+}
+
+// Nombres de materiales de la biblioteca para cada parte del terreno
+#[derive(Debug, Clone)]
+pub struct TerrainMaterials {
+    pub grass: String,
+    pub grass_dark: String,
+    pub water: String,
+    pub ground: String,                 // bloques bajo la superficie y fondo del agua
+}
+
+impl Default for TerrainMaterials {
+    fn default() -> Self {
+        TerrainMaterials {
+            grass: "grass".to_string(),
+            grass_dark: "grass_dark".to_string(),
+            water: "water".to_string(),
+            ground: "grass".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Terrain {
+    pub seed: u64,
+    pub size: [usize; 2],
+    pub tile: f32,
+    pub height: u32,
+    pub water_level: u32,
+    pub hill_size: f32,
+    pub rivers: u32,
+    pub river_width: f32,
+    pub ponds: u32,
+    pub trees: Vec<String>,
+    pub tree_density: f32,
+    pub materials: TerrainMaterials,
+}
+
+// Resultado de la generación, antes de volverse cubos
+#[derive(Debug, Clone, Copy)]
+struct Column {
+    ground: i32,                        // nivel del bloque de tierra más alto (-1 = sin tierra)
+    water: bool,                        // agua desde ground + 1 hasta water_level
+    dark: bool,                         // césped oscuro arriba
+}
+
+#[derive(Debug, Clone)]
+struct TreeSpot {
+    column: usize,
+    row: usize,
+    prefab: usize,                      // índice en `trees`
+    rotation: i32,
+}
+
+impl Terrain {
+    pub fn new(seed: u64, size: [usize; 2]) -> Self {
+        Terrain {
+            seed,
+            size,
+            tile: 1.0,
+            height: 5,
+            water_level: 1,
+            hill_size: 12.0,
+            rivers: 1,
+            river_width: 1.5,
+            ponds: 1,
+            trees: Vec::new(),
+            tree_density: 0.05,
+            materials: TerrainMaterials::default(),
+        }
+    }
+
+    pub fn with_height(mut self, height: u32, water_level: u32) -> Self {
+        self.height = height;
+        self.water_level = water_level;
+        self
+    }
+
+    pub fn with_water(mut self, rivers: u32, river_width: f32, ponds: u32) -> Self {
+        self.rivers = rivers;
+        self.river_width = river_width;
+        self.ponds = ponds;
+        self
+    }
+
+    pub fn with_trees(mut self, prefabs: Vec<String>, density: f32) -> Self {
+        self.trees = prefabs;
+        self.tree_density = density;
+        self
+    }
+
+    pub fn from_def(def: &TerrainDef) -> Result<Self, String> {
+        let field_err = |field: &str, message: String| format!("field '{}' {}", field, message);
+        if def.size[0] == 0 || def.size[1] == 0 {
+            return Err(field_err("size", format!("must be positive (got {:?})", def.size)));
+        }
+        let mut terrain = Terrain::new(def.seed, def.size);
+
+        let positive = |field: &str, value: Option<f32>, default: f32| -> Result<f32, String> {
+            let value = value.unwrap_or(default);
+            if !(value > 0.0 && value.is_finite()) {
+                return Err(field_err(field, format!("must be positive (got {})", value)));
+            }
+            Ok(value)
+        };
+        terrain.tile = positive("tile", def.tile, terrain.tile)?;
+        terrain.hill_size = positive("hill_size", def.hill_size, terrain.hill_size)?;
+        terrain.river_width = positive("river_width", def.river_width, terrain.river_width)?;
+
+        terrain.height = def.height.unwrap_or(terrain.height);
+        terrain.water_level = def.water_level.unwrap_or(terrain.water_level);
+        terrain.rivers = def.rivers.unwrap_or(terrain.rivers);
+        terrain.ponds = def.ponds.unwrap_or(terrain.ponds);
+        terrain.check_size().map_err(|m| field_err("size", m))?;
+
+        terrain.trees = def.trees.clone();
+        if let Some(density) = def.tree_density {
+            check_range(density, 0.0, 1.0).map_err(|m| field_err("tree_density", m))?;
+            terrain.tree_density = density;
+        }
+
+        let names = &def.materials;
+        let defaults = TerrainMaterials::default();
+        terrain.materials = TerrainMaterials {
+            grass: names.grass.clone().unwrap_or(defaults.grass),
+            grass_dark: names.grass_dark.clone().unwrap_or(defaults.grass_dark),
+            water: names.water.clone().unwrap_or(defaults.water),
+            ground: names.ground.clone().unwrap_or(defaults.ground),
+        };
+        Ok(terrain)
+    }
+
+    // Convierte el terreno en cubos; `offset` mueve todo el mapa en el mundo. Solo se
+    // generan los bloques de tierra que pueden verse (los tapados por sus vecinos no)
+    pub fn build(
+        &self,
+        materials: &MaterialLibrary,
+        prefabs: &HashMap<String, Prefab>,
+        offset: Vector3,
+    ) -> Result<Vec<Primitive>, String> {
+        self.check_size()?;
+        let tile = self.tile;
+        let [cols, rows] = self.size;
+        let x0 = -((cols as f32 - 1.0) * 0.5) * tile + offset.x;
+        let z0 = -((rows as f32 - 1.0) * 0.5) * tile + offset.z;
+        let y0 = -tile * 0.5 + offset.y; // centro de los bloques del nivel 0
+
+        let material_id = |name: &str| -> Result<MaterialId, String> {
+            materials.id(name).ok_or_else(|| format!("unknown material '{}'", name))
+        };
+        let grass = material_id(&self.materials.grass)?;
+        let grass_dark = material_id(&self.materials.grass_dark)?;
+        let water = material_id(&self.materials.water)?;
+        let ground = material_id(&self.materials.ground)?;
+        let tree_prefabs = self
+            .trees
+            .iter()
+            .map(|name| prefabs.get(name).ok_or_else(|| format!("unknown prefab '{}'", name)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let columns = self.columns();
+        let ground_at = |c: isize, r: isize| -> i32 {
+            if c < 0 || r < 0 || c as usize >= cols || r as usize >= rows {
+                -1
+            } else {
+                columns[r as usize * cols + c as usize].ground
+            }
+        };
+
+        let mut objects = Vec::new();
+        for r in 0..rows {
+            for c in 0..cols {
+                let column = columns[r * cols + c];
+                let x = x0 + c as f32 * tile;
+                let z = z0 + r as f32 * tile;
+                let (ci, ri) = (c as isize, r as isize);
+                let lowest_neighbor = [(ci - 1, ri), (ci + 1, ri), (ci, ri - 1), (ci, ri + 1)]
+                    .into_iter()
+                    .map(|(nc, nr)| ground_at(nc, nr))
+                    .min()
+                    .unwrap_or(-1);
+
+                for level in (lowest_neighbor + 1).min(column.ground).max(0)..=column.ground {
+                    let material_id = if level < column.ground || column.water {
+                        ground
+                    } else if column.dark {
+                        grass_dark
+                    } else {
+                        grass
+                    };
+                    let center = Vector3::new(x, y0 + level as f32 * tile, z);
+                    objects.push(Cube { center, size: tile, material_id }.into());
+                }
+                if column.water {
+                    for level in column.ground + 1..=self.water_level as i32 {
+                        let center = Vector3::new(x, y0 + level as f32 * tile, z);
+                        objects.push(Cube { center, size: tile, material_id: water }.into());
+                    }
+                }
+            }
+        }
+
+        for spot in self.tree_spots(&columns) {
+            let column = columns[spot.row * cols + spot.column];
+            let position = Vector3::new(
+                x0 + spot.column as f32 * tile,
+                y0 + column.ground as f32 * tile,
+                z0 + spot.row as f32 * tile,
+            );
+            let placement = Placement::at(position).with_scale(tile).with_rotation(spot.rotation)?;
+            tree_prefabs[spot.prefab]
+                .place(&placement, materials, &mut objects)
+                .map_err(|e| format!("prefab '{}': {}", self.trees[spot.prefab], e))?;
+        }
+        Ok(objects)
+    }
+
+    // Mismo tope de celdas que las grillas de voxeles: columnas x niveles x filas
+    fn check_size(&self) -> Result<(), String> {
+        let [cols, rows] = self.size;
+        check_size([cols, self.height as usize + 1, rows])
+    }
+
+    // Altura, agua y tono de cada columna, fila por fila
+    fn columns(&self) -> Vec<Column> {
+        let [cols, rows] = self.size;
+        let height_noise = Perlin::new(self.seed);
+        let tone_noise = Perlin::new(self.seed.wrapping_add(1));
+        let water_level = self.water_level as i32;
+        let bed = (water_level - 1).max(0);     // con water_level = 0 no se cava bajo el nivel 0
+
+        // el ruido vale 0 en los puntos enteros de la red: se muestrea corrido medio paso en y
+        let sample = |noise: &Perlin, c: f32, r: f32, frequency: f32, octaves: u32| {
+            noise.fbm(Vector3::new(c * frequency, 0.5, r * frequency), octaves, 2.0, 0.5)
+        };
+
+        let mut columns = Vec::with_capacity(cols * rows);
+        for r in 0..rows {
+            for c in 0..cols {
+                let (cf, rf) = (c as f32, r as f32);
+                // fbm rara vez pasa de ±0.7; se estira para usar toda la altura
+                let relief = (sample(&height_noise, cf, rf, 1.0 / self.hill_size, 4) * 0.7 + 0.5).clamp(0.0, 1.0);
+                let tone = sample(&tone_noise, cf, rf, 2.0 / self.hill_size, 2) + (relief - 0.5) * 0.5;
+                columns.push(Column {
+                    ground: (relief * self.height as f32).round() as i32,
+                    water: false,
+                    dark: tone > 0.05,
+                });
+            }
+        }
+
+        // ríos: la curva de nivel cero de otro ruido serpentea por todo el mapa. La distancia
+        // a la curva se estima como |ruido| / |gradiente|
+        for river in 0..self.rivers {
+            let noise = Perlin::new(self.seed.wrapping_add(100 + river as u64));
+            let frequency = 0.5 / self.hill_size;
+            for r in 0..rows {
+                for c in 0..cols {
+                    let (cf, rf) = (c as f32, r as f32);
+                    let value = sample(&noise, cf, rf, frequency, 2);
+                    let dx = sample(&noise, cf + 0.5, rf, frequency, 2) - sample(&noise, cf - 0.5, rf, frequency, 2);
+                    let dz = sample(&noise, cf, rf + 0.5, frequency, 2) - sample(&noise, cf, rf - 0.5, frequency, 2);
+                    let slope = (dx * dx + dz * dz).sqrt().max(1e-6);
+                    let distance = value.abs() / slope;
+
+                    let column = &mut columns[r * cols + c];
+                    if distance < self.river_width * 0.5 {
+                        column.ground = column.ground.min(bed);
+                    } else if distance < self.river_width * 0.5 + 1.0 {
+                        // orillas a ras del agua
+                        column.ground = column.ground.min(water_level);
+                    }
+                }
+            }
+        }
+
+        // estanques: hoyos redondos de borde irregular en lugares al azar
+        let mut rng = Rng::new(self.rng_seed(1));
+        for _ in 0..self.ponds {
+            let center_c = rng.next_f32() * cols as f32;
+            let center_r = rng.next_f32() * rows as f32;
+            let radius = self.hill_size * (0.15 + 0.15 * rng.next_f32());
+            for r in 0..rows {
+                for c in 0..cols {
+                    let (dc, dr) = (c as f32 - center_c, r as f32 - center_r);
+                    let wobble = sample(&tone_noise, c as f32, r as f32, 0.5, 1) * radius * 0.5;
+                    let distance = (dc * dc + dr * dr).sqrt() + wobble;
+                    let column = &mut columns[r * cols + c];
+                    if distance < radius {
+                        column.ground = column.ground.min(bed);
+                    } else if distance < radius + 1.0 {
+                        column.ground = column.ground.min(water_level);
+                    }
+                }
+            }
+        }
+
+        // todo lo que quedó por debajo del nivel del agua se inunda
+        for column in &mut columns {
+            column.water = column.ground < water_level;
+        }
+        columns
+    }
+
+    // Árboles sobre césped seco, lejos del agua y separados entre sí
+    fn tree_spots(&self, columns: &[Column]) -> Vec<TreeSpot> {
+        const SPACING: usize = 3;
+        if self.trees.is_empty() {
+            return Vec::new();
+        }
+        let [cols, rows] = self.size;
+        let near_water = |c: usize, r: usize| {
+            (r.saturating_sub(1)..=(r + 1).min(rows - 1))
+                .any(|nr| (c.saturating_sub(1)..=(c + 1).min(cols - 1)).any(|nc| columns[nr * cols + nc].water))
+        };
+
+        let mut rng = Rng::new(self.rng_seed(2));
+        let mut spots: Vec<TreeSpot> = Vec::new();
+        for r in 0..rows {
+            for c in 0..cols {
+                // se sortea en todas las columnas para que cada una use siempre los mismos números
+                let roll = rng.next_f32();
+                let prefab = rng.next_u32() as usize % self.trees.len();
+                let rotation = (rng.next_u32() % 4) as i32 * 90;
+                if roll >= self.tree_density || near_water(c, r) {
+                    continue;
+                }
+                let crowded = spots
+                    .iter()
+                    .any(|spot| spot.column.abs_diff(c) < SPACING && spot.row.abs_diff(r) < SPACING);
+                if !crowded {
+                    spots.push(TreeSpot { column: c, row: r, prefab, rotation });
+                }
+            }
+        }
+        spots
+    }
+
+    // Semilla de 32 bits para cada sorteo (estanques, árboles...) a partir de la del terreno
+    fn rng_seed(&self, stream: u64) -> u32 {
+        let mixed = self.seed.wrapping_add(stream.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        (mixed ^ (mixed >> 32)) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::TextureManager;
+    use std::path::Path;
+
+    fn library() -> MaterialLibrary {
+        let source = r#"
+            grass = { diffuse = [0.3, 0.6, 0.2] }
+            grass_dark = { diffuse = [0.1, 0.3, 0.1] }
+            water = { diffuse = [0.1, 0.3, 0.6] }
+        "#;
+        MaterialLibrary::from_toml_str(source, Path::new(""), &mut TextureManager::new()).unwrap()
+    }
+
+    fn blocks(terrain: &Terrain, materials: &MaterialLibrary) -> Vec<(Vector3, MaterialId)> {
+        let objects = terrain.build(materials, &HashMap::new(), Vector3::zero()).unwrap();
+        objects.iter().map(|object| (object.center(), object.material_id())).collect()
+    }
+
+    #[test]
+    fn same_seed_same_world() {
+        let materials = library();
+        let first = blocks(&Terrain::new(7, [24, 24]), &materials);
+        let second = blocks(&Terrain::new(7, [24, 24]), &materials);
+        assert!(!first.is_empty());
+        assert_eq!(first, second);
+
+        let other = blocks(&Terrain::new(8, [24, 24]), &materials);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn water_columns_use_the_water_material() {
+        let materials = library();
+        let water = materials.id("water").unwrap();
+        let terrain = Terrain::new(7, [24, 24]);
+        let columns = terrain.columns();
+        assert!(columns.iter().any(|column| column.water), "seed 7 should have water");
+
+        // con tile 1 y sin offset, la columna (c, r) queda en x = c - 11.5, z = r - 11.5
+        for (center, material_id) in blocks(&terrain, &materials) {
+            let c = (center.x + 11.5).round() as usize;
+            let r = (center.z + 11.5).round() as usize;
+            let column = columns[r * 24 + c];
+            let level = (center.y + 0.5).round() as i32;
+            let is_water_block = column.water && level > column.ground;
+            assert_eq!(material_id == water, is_water_block, "column ({}, {}) level {}", c, r, level);
+        }
+    }
+
+    #[test]
+    fn no_water_level_keeps_ground_at_zero() {
+        let terrain = Terrain::new(7, [24, 24]).with_height(5, 0);
+        for column in terrain.columns() {
+            assert!(column.ground >= 0);
+            assert!(!column.water);
+        }
+    }
+
+    #[test]
+    fn rejects_huge_terrains() {
+        let materials = library();
+        let error = Terrain::new(7, [1 << 20, 1 << 20]).build(&materials, &HashMap::new(), Vector3::zero()).err().unwrap();
+        assert!(error.contains("too large"), "{}", error);
+
+        let def: TerrainDef = toml::from_str("seed = 7\nsize = [4096, 4096]\nheight = 100").unwrap();
+        let error = Terrain::from_def(&def).err().unwrap();
+        assert!(error.starts_with("field 'size'"), "{}", error);
+    }
+}
@@ -1,6 +1,16 @@
 // texture.rs
-use raylib::prelude::*;
+use crate::math::{Color, Vector2, Vector3};
 use std::collections::HashMap;
+use crate::procedural::{ProceduralDef, ProceduralTexture};
+use serde::Deserialize;
+
+// "archivo.png" | { pattern = "checker" | "noise" | "fbm" | "marble" | "wood", ... }
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum TextureDef {
+    Image(String),
+    Procedural(ProceduralDef),
+}
 
 pub struct Texture {
     pub width: i32,
@@ -9,16 +19,30 @@ pub struct Texture {
 }
 
 impl Texture {
-    pub fn new(image: &Image) -> Self {
-    Texture {
-        width: image.width(),
-        height: image.height(),
-        // get_image_data() devuelve un slice, lo convertimos a un Vec
-        data: image.get_image_data().to_vec(),
+    // Decodifica con el crate `image` (PNG, JPEG, TGA, BMP, HDR): no hace falta raylib ni ventana
+    pub fn load(file_path: &str) -> Result<Self, String> {
+        let image = image::open(file_path).map_err(|e| format!("{}: {}", file_path, e))?;
+        Ok(Texture::from_image(&image))
+    }
+
+    pub fn from_image(image: &image::DynamicImage) -> Self {
+        // HDR y formatos de 16 bits se reducen a RGBA8 (se recortan a [0, 1])
+        let rgba = image.to_rgba8();
+        Texture {
+            width: rgba.width() as i32,
+            height: rgba.height() as i32,
+            data: rgba
+                .pixels()
+                .map(|p| Color::new(p[0], p[1], p[2], p[3]))
+                .collect(),
         }
     }
     
     pub fn get_color(&self, u: f32, v: f32) -> Color {
+        // Fuera de [0, 1] la textura se repite (UV animadas o escaladas)
+        let u = if (0.0..=1.0).contains(&u) { u } else { u - u.floor() };
+        let v = if (0.0..=1.0).contains(&v) { v } else { v - v.floor() };
+
         let x = (u * (self.width - 1) as f32).round() as i32;
         let y = ((1.0 - v) * (self.height - 1) as f32).round() as i32; // Invertimos v porque las coordenadas de textura suelen empezar desde abajo
 
@@ -31,31 +55,67 @@ impl Texture {
 
 pub struct TextureManager {
     textures: HashMap<usize, Texture>,
+    procedurals: HashMap<usize, ProceduralTexture>, // comparten ids con las imágenes
+    paths: HashMap<usize, String>,                  // archivo de cada imagen (para exportar)
     next_id: usize,
 }
 
+impl Default for TextureManager {
+    fn default() -> Self {
+        TextureManager::new()
+    }
+}
+
 impl TextureManager {
     pub fn new() -> Self {
         TextureManager {
             textures: HashMap::new(),
+            procedurals: HashMap::new(),
+            paths: HashMap::new(),
             next_id: 0,
         }
     }
 
     pub fn load_texture(&mut self, file_path: &str) -> Result<usize, String> {
-        let image = match Image::load_image(file_path) {
-            Ok(img) => img,
-            Err(e) => return Err(e.to_string()),
-        };
+        let id = self.add_texture(Texture::load(file_path)?);
+        self.paths.insert(id, file_path.to_string());
+        Ok(id)
+    }
 
-        let texture = Texture::new(&image);
+    // Imagen ya decodificada (o armada a mano) sin archivo detrás
+    pub fn add_texture(&mut self, texture: Texture) -> usize {
         let id = self.next_id;
         self.textures.insert(id, texture);
         self.next_id += 1;
-        Ok(id)
+        id
+    }
+
+    pub fn add_procedural(&mut self, texture: ProceduralTexture) -> usize {
+        let id = self.next_id;
+        self.procedurals.insert(id, texture);
+        self.next_id += 1;
+        id
     }
 
     pub fn get_texture(&self, id: usize) -> Option<&Texture> {
         self.textures.get(&id)
     }
+
+    // Ruta con la que se cargó la imagen `id`; None para las procedurales
+    pub fn path(&self, id: usize) -> Option<&str> {
+        self.paths.get(&id).map(|path| path.as_str())
+    }
+
+    pub fn get_procedural(&self, id: usize) -> Option<&ProceduralTexture> {
+        self.procedurals.get(&id)
+    }
+
+    // Color de la textura `id` en el impacto: las imágenes necesitan UV,
+    // las procedurales pueden evaluarse en la UV o en el punto 3D
+    pub fn sample(&self, id: usize, uv: Option<Vector2>, point: Vector3) -> Option<Color> {
+        if let Some(texture) = self.textures.get(&id) {
+            return uv.map(|uv| texture.get_color(uv.x, uv.y));
+        }
+        self.procedurals.get(&id).map(|procedural| procedural.sample(uv, point))
+    }
 }
\ No newline at end of file
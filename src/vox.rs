@@ -0,0 +1,463 @@
+// vox.rs
+// Importa archivos .vox de MagicaVoxel: modelos (SIZE + XYZI), paleta (RGBA, o la paleta
+// por defecto si falta) y el grafo de escena (nTRN / nGRP / nSHP) con traslaciones y
+// rotaciones. Los demás chunks (MATL, LAYR, rOBJ, ...) se ignoran.
+//
+// MagicaVoxel usa z hacia arriba; al importar se pasa a y hacia arriba: (x, y, z) -> (x, z, -y).
+use std::collections::HashMap;
+
+use crate::voxel_grid::{Palette, VoxelGrid};
+
+struct VoxModel {
+    size: [i32; 3],
+    voxels: Vec<([i32; 3], u8)>,
+}
+
+enum SceneNode {
+    Transform { child: i32, transform: Transform },
+    Group { children: Vec<i32> },
+    Shape { models: Vec<i32> },
+}
+
+// Rotación (matriz entera, filas) y traslación en coordenadas de MagicaVoxel. Se opera en
+// i64 para que traslaciones cerca de ±2^31 no desborden; al final se comprueba el rango
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Transform {
+    rotation: [[i64; 3]; 3],
+    translation: [i64; 3],
+}
+
+impl Transform {
+    const IDENTITY: Transform = Transform {
+        rotation: [[1, 0, 0], [0, 1, 0], [0, 0, 1]],
+        translation: [0, 0, 0],
+    };
+
+    fn rotate(&self, v: [i64; 3]) -> [i64; 3] {
+        let r = &self.rotation;
+        [
+            r[0][0] * v[0] + r[0][1] * v[1] + r[0][2] * v[2],
+            r[1][0] * v[0] + r[1][1] * v[1] + r[1][2] * v[2],
+            r[2][0] * v[0] + r[2][1] * v[1] + r[2][2] * v[2],
+        ]
+    }
+
+    fn apply(&self, v: [i64; 3]) -> [i64; 3] {
+        let r = self.rotate(v);
+        [r[0] + self.translation[0], r[1] + self.translation[1], r[2] + self.translation[2]]
+    }
+
+    // self ∘ child: primero el hijo, luego el padre
+    fn then(&self, child: &Transform) -> Transform {
+        let mut rotation = [[0; 3]; 3];
+        for (i, row) in rotation.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = (0..3).map(|k| self.rotation[i][k] * child.rotation[k][j]).sum();
+            }
+        }
+        Transform { rotation, translation: self.apply(child.translation) }
+    }
+}
+
+pub fn load_vox(file_path: &str) -> Result<VoxelGrid, String> {
+    let bytes = std::fs::read(file_path).map_err(|e| format!("{}: {}", file_path, e))?;
+    parse_vox(&bytes).map_err(|e| format!("{}: {}", file_path, e))
+}
+
+pub fn parse_vox(bytes: &[u8]) -> Result<VoxelGrid, String> {
+    let mut reader = Reader { bytes, position: 0 };
+    if reader.take(4)? != b"VOX " {
+        return Err("not a MagicaVoxel file (missing \"VOX \" header)".to_string());
+    }
+    let _version = reader.i32()?;
+
+    let (id, content_size, _) = reader.chunk_header()?;
+    if id != *b"MAIN" {
+        return Err(format!("expected MAIN chunk, found {}", String::from_utf8_lossy(&id)));
+    }
+    reader.take(content_size)?;
+
+    let mut models: Vec<VoxModel> = Vec::new();
+    let mut pending_size: Option<[i32; 3]> = None;
+    let mut palette: Option<Palette> = None;
+    let mut nodes: HashMap<i32, SceneNode> = HashMap::new();
+
+    // los hijos de MAIN van seguidos hasta el final del archivo
+    while reader.position < bytes.len() {
+        let (id, content_size, children_size) = reader.chunk_header()?;
+        let mut content = Reader { bytes: reader.take(content_size)?, position: 0 };
+        reader.take(children_size)?;
+
+        match &id {
+            b"SIZE" => pending_size = Some([content.i32()?, content.i32()?, content.i32()?]),
+            b"XYZI" => {
+                let size = pending_size.take().ok_or("XYZI chunk without a preceding SIZE")?;
+                let count = content.count()?;
+                // 4 bytes por voxel: no se reserva más de lo que cabe en el chunk
+                if count > content.remaining() / 4 {
+                    return Err(format!("XYZI chunk declares {} voxels but holds {} bytes", count, content.remaining()));
+                }
+                let mut voxels = Vec::with_capacity(count);
+                for _ in 0..count {
+                    let v = content.take(4)?;
+                    if v[3] != 0 {
+                        voxels.push(([v[0] as i32, v[1] as i32, v[2] as i32], v[3]));
+                    }
+                }
+                models.push(VoxModel { size, voxels });
+            }
+            b"RGBA" => {
+                let mut colors = [[0u8; 4]; 256];
+                // el color i del chunk es el índice i + 1; el último no se usa
+                for color in colors.iter_mut().skip(1) {
+                    let c = content.take(4)?;
+                    *color = [c[0], c[1], c[2], c[3]];
+                }
+                palette = Some(colors);
+            }
+            b"nTRN" => {
+                let node_id = content.i32()?;
+                content.dict()?;
+                let child = content.i32()?;
+                let _reserved = content.i32()?;
+                let _layer = content.i32()?;
+                let frames = content.count()?;
+                let mut transform = Transform::IDENTITY;
+                for frame in 0..frames {
+                    let attributes = content.dict()?;
+                    if frame == 0 {
+                        transform = frame_transform(&attributes)?;
+                    }
+                }
+                nodes.insert(node_id, SceneNode::Transform { child, transform });
+            }
+            b"nGRP" => {
+                let node_id = content.i32()?;
+                content.dict()?;
+                let count = content.count()?;
+                let children = (0..count).map(|_| content.i32()).collect::<Result<_, _>>()?;
+                nodes.insert(node_id, SceneNode::Group { children });
+            }
+            b"nSHP" => {
+                let node_id = content.i32()?;
+                content.dict()?;
+                let count = content.count()?;
+                let mut shape_models = Vec::with_capacity(count.min(content.remaining() / 4));
+                for _ in 0..count {
+                    shape_models.push(content.i32()?);
+                    content.dict()?;
+                }
+                nodes.insert(node_id, SceneNode::Shape { models: shape_models });
+            }
+            _ => {}
+        }
+    }
+
+    let mut voxels = Vec::new();
+    if nodes.is_empty() {
+        // archivos sin grafo de escena: todos los modelos centrados en el origen
+        for model in &models {
+            place_model(model, &Transform::IDENTITY, &mut voxels)?;
+        }
+    } else {
+        walk(0, &Transform::IDENTITY, &nodes, &models, &mut voxels, 0)?;
+    }
+
+    VoxelGrid::from_voxels(&voxels, palette.unwrap_or_else(default_palette))
+}
+
+fn walk(
+    node_id: i32,
+    parent: &Transform,
+    nodes: &HashMap<i32, SceneNode>,
+    models: &[VoxModel],
+    voxels: &mut Vec<([i32; 3], u8)>,
+    depth: usize,
+) -> Result<(), String> {
+    if depth > nodes.len() {
+        return Err("scene graph has a cycle".to_string());
+    }
+    match nodes.get(&node_id) {
+        Some(SceneNode::Transform { child, transform }) => {
+            walk(*child, &parent.then(transform), nodes, models, voxels, depth + 1)
+        }
+        Some(SceneNode::Group { children }) => {
+            for child in children {
+                walk(*child, parent, nodes, models, voxels, depth + 1)?;
+            }
+            Ok(())
+        }
+        Some(SceneNode::Shape { models: shape_models }) => {
+            for model_id in shape_models {
+                let model = usize::try_from(*model_id)
+                    .ok()
+                    .and_then(|i| models.get(i))
+                    .ok_or_else(|| format!("shape node {} uses missing model {}", node_id, model_id))?;
+                place_model(model, parent, voxels)?;
+            }
+            Ok(())
+        }
+        None => Err(format!("scene graph references missing node {}", node_id)),
+    }
+}
+
+// El pivote de cada modelo es el centro de su caja (redondeado hacia abajo)
+fn place_model(model: &VoxModel, transform: &Transform, voxels: &mut Vec<([i32; 3], u8)>) -> Result<(), String> {
+    let pivot = model.size.map(|s| s as i64 / 2);
+    for (position, index) in &model.voxels {
+        let local = [0, 1, 2].map(|axis| position[axis] as i64 - pivot[axis]);
+        let [x, y, z] = transform.apply(local);
+        let [Ok(wx), Ok(wy), Ok(wz)] = [x, z, -y].map(i32::try_from) else {
+            return Err(format!("voxel at ({}, {}, {}) is out of range", x, z, -y));
+        };
+        voxels.push(([wx, wy, wz], *index));
+    }
+    Ok(())
+}
+
+// Atributos de un frame de nTRN: "_r" rotación empaquetada en un byte, "_t" traslación "x y z"
+fn frame_transform(attributes: &HashMap<String, String>) -> Result<Transform, String> {
+    let mut transform = Transform::IDENTITY;
+    if let Some(text) = attributes.get("_t") {
+        let parts: Vec<i32> = text
+            .split_whitespace()
+            .map(|p| p.parse::<i32>().map_err(|_| format!("bad translation \"{}\"", text)))
+            .collect::<Result<_, _>>()?;
+        if parts.len() != 3 {
+            return Err(format!("bad translation \"{}\"", text));
+        }
+        transform.translation = [parts[0] as i64, parts[1] as i64, parts[2] as i64];
+    }
+    if let Some(text) = attributes.get("_r") {
+        let packed = text.trim().parse::<u8>().map_err(|_| format!("bad rotation \"{}\"", text))?;
+        transform.rotation = unpack_rotation(packed)?;
+    }
+    Ok(transform)
+}
+
+// Bits 0-1: columna del 1 en la fila 0; bits 2-3: en la fila 1; bits 4-6: signos de cada fila
+fn unpack_rotation(packed: u8) -> Result<[[i64; 3]; 3], String> {
+    let first = (packed & 3) as usize;
+    let second = ((packed >> 2) & 3) as usize;
+    if first > 2 || second > 2 || first == second {
+        return Err(format!("bad rotation {}", packed));
+    }
+    let third = 3 - first - second;
+    let mut rotation = [[0; 3]; 3];
+    for (row, column) in [first, second, third].into_iter().enumerate() {
+        rotation[row][column] = if packed & (1 << (4 + row)) != 0 { -1 } else { 1 };
+    }
+    Ok(rotation)
+}
+
+// Paleta que usa MagicaVoxel cuando el archivo no trae RGBA: el cubo de colores 6x6x6
+// (sin el negro) y rampas de azul, verde, rojo y gris
+pub fn default_palette() -> Palette {
+    const LEVELS: [u8; 6] = [0xff, 0xcc, 0x99, 0x66, 0x33, 0x00];
+    const RAMP: [u8; 10] = [0xee, 0xdd, 0xbb, 0xaa, 0x88, 0x77, 0x55, 0x44, 0x22, 0x11];
+
+    let mut palette = [[0u8; 4]; 256];
+    let mut index = 1;
+    for r in LEVELS {
+        for g in LEVELS {
+            for b in LEVELS {
+                if r == 0 && g == 0 && b == 0 {
+                    continue;
+                }
+                palette[index] = [r, g, b, 255];
+                index += 1;
+            }
+        }
+    }
+    for channel in [2, 1, 0] {
+        for value in RAMP {
+            let mut color = [0, 0, 0, 255];
+            color[channel] = value;
+            palette[index] = color;
+            index += 1;
+        }
+    }
+    for value in RAMP {
+        palette[index] = [value, value, value, 255];
+        index += 1;
+    }
+    palette
+}
+
+// Lector little-endian sobre un slice; también lo usa voxel_file.rs
+pub(crate) struct Reader<'a> {
+    pub(crate) bytes: &'a [u8],
+    pub(crate) position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        let end = self.position.checked_add(count).filter(|end| *end <= self.bytes.len());
+        let end = end.ok_or_else(|| "unexpected end of file".to_string())?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, String> {
+        let b = self.take(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, String> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub(crate) fn i32(&mut self) -> Result<i32, String> {
+        let b = self.take(4)?;
+        Ok(i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub(crate) fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    // Cantidad no negativa (número de voxeles, hijos, frames...)
+    fn count(&mut self) -> Result<usize, String> {
+        let value = self.i32()?;
+        usize::try_from(value).map_err(|_| format!("negative count {}", value))
+    }
+
+    fn chunk_header(&mut self) -> Result<([u8; 4], usize, usize), String> {
+        let id = self.take(4)?;
+        let content_size = self.count()?;
+        let children_size = self.count()?;
+        Ok(([id[0], id[1], id[2], id[3]], content_size, children_size))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let length = self.count()?;
+        Ok(String::from_utf8_lossy(self.take(length)?).into_owned())
+    }
+
+    fn dict(&mut self) -> Result<HashMap<String, String>, String> {
+        let count = self.count()?;
+        let mut dict = HashMap::with_capacity(count.min(64));
+        for _ in 0..count {
+            let key = self.string()?;
+            let value = self.string()?;
+            dict.insert(key, value);
+        }
+        Ok(dict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: &[u8; 4], content: &[u8], children: &[u8]) -> Vec<u8> {
+        let mut out = id.to_vec();
+        out.extend((content.len() as i32).to_le_bytes());
+        out.extend((children.len() as i32).to_le_bytes());
+        out.extend(content);
+        out.extend(children);
+        out
+    }
+
+    fn ints(values: &[i32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    fn dict(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut out = ints(&[entries.len() as i32]);
+        for (key, value) in entries {
+            for text in [key, value] {
+                out.extend(ints(&[text.len() as i32]));
+                out.extend(text.as_bytes());
+            }
+        }
+        out
+    }
+
+    // nTRN: id, atributos, hijo, reservado, capa, frames
+    fn transform(node_id: i32, child: i32, frame: &[(&str, &str)]) -> Vec<u8> {
+        let mut content = ints(&[node_id]);
+        content.extend(dict(&[]));
+        content.extend(ints(&[child, -1, 0, 1]));
+        content.extend(dict(frame));
+        chunk(b"nTRN", &content, &[])
+    }
+
+    fn vox_file(xyzi: &[u8], translation: &str) -> Vec<u8> {
+        let mut palette = vec![0u8; 256 * 4];
+        palette[..8].copy_from_slice(&[255, 0, 0, 255, 0, 0, 255, 255]); // índices 1 y 2
+
+        let mut group = ints(&[1]);
+        group.extend(dict(&[]));
+        group.extend(ints(&[1, 2]));
+        let mut shape = ints(&[3]);
+        shape.extend(dict(&[]));
+        shape.extend(ints(&[1, 0]));
+        shape.extend(dict(&[]));
+
+        let mut children = chunk(b"SIZE", &ints(&[2, 2, 2]), &[]);
+        children.extend(chunk(b"XYZI", xyzi, &[]));
+        children.extend(chunk(b"RGBA", &palette, &[]));
+        children.extend(transform(0, 1, &[]));
+        children.extend(chunk(b"nGRP", &group, &[]));
+        // rotación 90° alrededor de z: fila 0 = -y, fila 1 = x  (columnas 1 y 0, signo en la fila 0)
+        children.extend(transform(2, 3, &[("_t", translation), ("_r", "17")]));
+        children.extend(chunk(b"nSHP", &shape, &[]));
+
+        let mut out = b"VOX ".to_vec();
+        out.extend(ints(&[150]));
+        out.extend(chunk(b"MAIN", &[], &children));
+        out
+    }
+
+    #[test]
+    fn parses_models_palette_and_transform_chain() {
+        let mut xyzi = ints(&[2]);
+        xyzi.extend([0, 0, 0, 1, 1, 0, 1, 2]);
+        let grid = parse_vox(&vox_file(&xyzi, "10 0 0")).unwrap();
+
+        assert_eq!(grid.voxel_count(), 2);
+        assert_eq!(grid.palette[1], [255, 0, 0, 255]);
+        assert_eq!(grid.palette[2], [0, 0, 255, 255]);
+        // pivote (1, 1, 1); (0,0,0) -> local (-1,-1,-1) -> rotado (1,-1,-1) -> +t (11,-1,-1) -> y arriba
+        assert_eq!(grid.get([11, -1, 1]), 1);
+        // (1,0,1) -> local (0,-1,0) -> rotado (1,0,0) -> (11,0,0)
+        assert_eq!(grid.get([11, 0, 0]), 2);
+    }
+
+    #[test]
+    fn rejects_a_voxel_count_larger_than_the_chunk() {
+        let mut xyzi = ints(&[i32::MAX]);
+        xyzi.extend([0, 0, 0, 1]);
+        let error = parse_vox(&vox_file(&xyzi, "10 0 0")).err().unwrap();
+        assert!(error.contains("XYZI chunk declares"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_voxels_translated_out_of_range() {
+        let mut xyzi = ints(&[1]);
+        xyzi.extend([1, 0, 1, 1]); // rotado queda en x = 1: con la traslación pasa de i32::MAX
+        let error = parse_vox(&vox_file(&xyzi, "2147483647 0 0")).err().unwrap();
+        assert!(error.contains("out of range"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_models_too_far_apart() {
+        let voxels = [([-2_000_000_000, 0, 0], 1), ([2_000_000_000, 0, 0], 1)];
+        let error = VoxelGrid::from_voxels(&voxels, default_palette()).err().unwrap();
+        assert!(error.contains("too large"), "{}", error);
+
+        let voxels = [([0, 0, 0], 1), ([4000, 4000, 4000], 1)];
+        assert!(VoxelGrid::from_voxels(&voxels, default_palette()).is_err());
+    }
+}
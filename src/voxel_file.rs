@@ -0,0 +1,404 @@
+// voxel_file.rs
+// Formato binario propio para guardar mundos de voxeles grandes (extensión .rvx): la tabla
+// de paleta con sus materiales y las celdas partidas en chunks comprimidos con RLE.
+//
+//     "RVOX", u16 versión
+//     u32 x 3 tamaño, i32 x 3 origen, u16 lado del chunk
+//     u16 entradas de paleta; cada una: u8 índice, RGBA, u8 banderas
+//         (1 material, 2 transparencia, 4 reflectividad, 8 hay override aunque esté vacío)
+//         y los campos marcados (material: u16 largo + UTF-8; los otros dos f32)
+//     u32 chunks; cada uno: u32 x 3 posición (en chunks), u32 corridas
+//         y las corridas: largo (varint LEB128) + índice de paleta
+//
+// Todo en little-endian. Los chunks vacíos no se guardan y dentro de un chunk las celdas
+// van en el mismo orden que en VoxelGrid (x más rápido, luego z, luego y). La rejilla tiene
+// a lo sumo MAX_CELLS celdas (ver voxel_grid.rs).
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::vox::Reader;
+use crate::voxel_grid::{PaletteOverride, VoxelGrid, check_size};
+
+const MAGIC: &[u8; 4] = b"RVOX";
+pub const VERSION: u16 = 1;
+const CHUNK_SIZE: usize = 32;
+
+const HAS_MATERIAL: u8 = 1;
+const HAS_TRANSPARENCY: u8 = 2;
+const HAS_REFLECTIVITY: u8 = 4;
+const HAS_OVERRIDE: u8 = 8;
+
+// Rejilla más los cambios de material por índice de paleta (los mismos que acepta `build`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoxelWorld {
+    pub grid: VoxelGrid,
+    pub overrides: HashMap<u8, PaletteOverride>,
+}
+
+impl VoxelWorld {
+    pub fn new(grid: VoxelGrid) -> Self {
+        VoxelWorld { grid, overrides: HashMap::new() }
+    }
+
+    pub fn with_override(mut self, index: u8, palette_override: PaletteOverride) -> Self {
+        self.overrides.insert(index, palette_override);
+        self
+    }
+
+    pub fn load(file_path: &str) -> Result<Self, String> {
+        let bytes = std::fs::read(file_path).map_err(|e| format!("{}: {}", file_path, e))?;
+        Self::from_bytes(&bytes).map_err(|e| format!("{}: {}", file_path, e))
+    }
+
+    pub fn save(&self, file_path: &str) -> Result<(), String> {
+        let bytes = self.to_bytes().map_err(|e| format!("{}: {}", file_path, e))?;
+        std::fs::write(file_path, bytes).map_err(|e| format!("{}: {}", file_path, e))
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let grid = &self.grid;
+        check_size(grid.size)?;
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        for (axis, size) in grid.size.iter().enumerate() {
+            let size = u32::try_from(*size).map_err(|_| format!("grid is too large on axis {} ({} cells)", axis, size))?;
+            out.extend_from_slice(&size.to_le_bytes());
+        }
+        for origin in grid.origin {
+            out.extend_from_slice(&origin.to_le_bytes());
+        }
+        out.extend_from_slice(&(CHUNK_SIZE as u16).to_le_bytes());
+
+        // solo las entradas con color o con override
+        let entries: Vec<u8> = (0..=255u8)
+            .filter(|i| grid.palette[*i as usize] != [0; 4] || self.overrides.contains_key(i))
+            .collect();
+        out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        for index in entries {
+            out.push(index);
+            out.extend_from_slice(&grid.palette[index as usize]);
+            let palette_override = self.overrides.get(&index).cloned().unwrap_or_default();
+            let mut flags = if self.overrides.contains_key(&index) { HAS_OVERRIDE } else { 0 };
+            if palette_override.material.is_some() {
+                flags |= HAS_MATERIAL;
+            }
+            if palette_override.transparency.is_some() {
+                flags |= HAS_TRANSPARENCY;
+            }
+            if palette_override.reflectivity.is_some() {
+                flags |= HAS_REFLECTIVITY;
+            }
+            out.push(flags);
+            if let Some(name) = &palette_override.material {
+                let length = u16::try_from(name.len())
+                    .map_err(|_| format!("palette index {}: material name is too long", index))?;
+                out.extend_from_slice(&length.to_le_bytes());
+                out.extend_from_slice(name.as_bytes());
+            }
+            if let Some(v) = palette_override.transparency {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            if let Some(v) = palette_override.reflectivity {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+
+        // la cantidad de chunks se escribe al final, cuando se sabe cuántos no están vacíos
+        let count_position = out.len();
+        out.extend_from_slice(&0u32.to_le_bytes());
+        let mut chunk_count = 0u32;
+        let mut runs: Vec<(u32, u8)> = Vec::new();
+        let chunks = chunk_counts(grid.size, CHUNK_SIZE);
+        for cy in 0..chunks[1] {
+            for cz in 0..chunks[2] {
+                for cx in 0..chunks[0] {
+                    let chunk = [cx, cy, cz];
+                    runs.clear();
+                    for row in chunk_rows(grid.size, chunk, CHUNK_SIZE) {
+                        for &index in &grid.cells[row] {
+                            match runs.last_mut() {
+                                Some((length, value)) if *value == index => *length += 1,
+                                _ => runs.push((1, index)),
+                            }
+                        }
+                    }
+                    if runs.len() == 1 && runs[0].1 == 0 {
+                        continue;
+                    }
+
+                    for c in chunk {
+                        out.extend_from_slice(&(c as u32).to_le_bytes());
+                    }
+                    out.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+                    for (length, index) in &runs {
+                        write_varint(&mut out, *length);
+                        out.push(*index);
+                    }
+                    chunk_count += 1;
+                }
+            }
+        }
+        out[count_position..count_position + 4].copy_from_slice(&chunk_count.to_le_bytes());
+        Ok(out)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { bytes, position: 0 };
+        if reader.take(4)? != MAGIC {
+            return Err("not a voxel world file (missing \"RVOX\" header)".to_string());
+        }
+        let version = reader.u16()?;
+        if version != VERSION {
+            return Err(format!("unsupported version {} (expected {})", version, VERSION));
+        }
+        let size = [reader.u32()? as usize, reader.u32()? as usize, reader.u32()? as usize];
+        let origin = [reader.i32()?, reader.i32()?, reader.i32()?];
+        let chunk_size = reader.u16()? as usize;
+        if chunk_size == 0 {
+            return Err("chunk size must be positive".to_string());
+        }
+        check_size(size)?;
+
+        let mut palette = [[0u8; 4]; 256];
+        let mut overrides = HashMap::new();
+        let entries = reader.u16()?;
+        for _ in 0..entries {
+            let index = reader.u8()?;
+            let c = reader.take(4)?;
+            palette[index as usize] = [c[0], c[1], c[2], c[3]];
+            let flags = reader.u8()?;
+            if flags & !(HAS_MATERIAL | HAS_TRANSPARENCY | HAS_REFLECTIVITY | HAS_OVERRIDE) != 0 {
+                return Err(format!("palette index {}: unknown flags {:#04x}", index, flags));
+            }
+            if flags == 0 {
+                continue;
+            }
+            let mut palette_override = PaletteOverride::default();
+            if flags & HAS_MATERIAL != 0 {
+                let length = reader.u16()? as usize;
+                let name = std::str::from_utf8(reader.take(length)?)
+                    .map_err(|_| format!("palette index {}: material name is not valid UTF-8", index))?;
+                palette_override.material = Some(name.to_string());
+            }
+            if flags & HAS_TRANSPARENCY != 0 {
+                palette_override.transparency = Some(reader.f32()?);
+            }
+            if flags & HAS_REFLECTIVITY != 0 {
+                palette_override.reflectivity = Some(reader.f32()?);
+            }
+            overrides.insert(index, palette_override);
+        }
+
+        let mut grid = VoxelGrid::new(size, origin, palette);
+        let chunks = chunk_counts(size, chunk_size);
+        let chunk_count = reader.u32()?;
+        let mut cells: Vec<u8> = Vec::new();
+        for _ in 0..chunk_count {
+            let chunk = [reader.u32()? as usize, reader.u32()? as usize, reader.u32()? as usize];
+            let label = format!("chunk ({}, {}, {})", chunk[0], chunk[1], chunk[2]);
+            if (0..3).any(|axis| chunk[axis] >= chunks[axis]) {
+                return Err(format!("{} is outside the grid", label));
+            }
+            let rows = chunk_rows(size, chunk, chunk_size);
+            let expected: usize = rows.iter().map(|row| row.len()).sum();
+
+            cells.clear();
+            let run_count = reader.u32()?;
+            for _ in 0..run_count {
+                let length = read_varint(&mut reader)? as usize;
+                let index = reader.u8()?;
+                if cells.len() + length > expected {
+                    return Err(format!("{}: runs cover more than {} cells", label, expected));
+                }
+                cells.resize(cells.len() + length, index);
+            }
+            if cells.len() != expected {
+                return Err(format!("{}: runs cover {} cells, expected {}", label, cells.len(), expected));
+            }
+
+            let mut start = 0;
+            for row in rows {
+                let end = start + row.len();
+                grid.cells[row].copy_from_slice(&cells[start..end]);
+                start = end;
+            }
+        }
+
+        if reader.position != bytes.len() {
+            return Err(format!("{} unexpected bytes after the last chunk", bytes.len() - reader.position));
+        }
+        Ok(VoxelWorld { grid, overrides })
+    }
+}
+
+// Chunks por eje (el último de cada eje puede quedar incompleto)
+fn chunk_counts(size: [usize; 3], chunk_size: usize) -> [usize; 3] {
+    size.map(|cells| cells.div_ceil(chunk_size))
+}
+
+// Tramos de `cells` que ocupa un chunk: una fila de x por cada (y, z), en orden
+fn chunk_rows(size: [usize; 3], chunk: [usize; 3], chunk_size: usize) -> Vec<Range<usize>> {
+    let range = |axis: usize| {
+        let start = chunk[axis] * chunk_size;
+        start..(start + chunk_size).min(size[axis])
+    };
+    let (xs, ys, zs) = (range(0), range(1), range(2));
+    let mut rows = Vec::with_capacity(ys.len() * zs.len());
+    for y in ys {
+        for z in zs.clone() {
+            let first = (y * size[2] + z) * size[0];
+            rows.push(first + xs.start..first + xs.end);
+        }
+    }
+    rows
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        out.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(reader: &mut Reader) -> Result<u32, String> {
+    let mut value = 0u32;
+    for shift in (0..32).step_by(7) {
+        let byte = reader.u8()?;
+        if shift == 28 && byte > 0x0f {
+            return Err("run length does not fit in 32 bits".to_string());
+        }
+        value |= ((byte & 0x7f) as u32) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err("run length does not fit in 32 bits".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vox::default_palette;
+
+    fn sample_world() -> VoxelWorld {
+        // 70 x 40 x 33 cruza bordes de chunk en los tres ejes
+        let mut grid = VoxelGrid::new([70, 40, 33], [-35, -3, 10], default_palette());
+        for (i, cell) in grid.cells.iter_mut().enumerate() {
+            // terreno macizo abajo, bandas y algo de ruido arriba
+            let y = i / (70 * 33);
+            *cell = match y {
+                0..=4 => 1 + (y as u8),
+                5..=20 if i % 7 == 0 => (i % 255) as u8 + 1,
+                _ => 0,
+            };
+        }
+        VoxelWorld::new(grid)
+            .with_override(3, PaletteOverride { material: Some("water".to_string()), ..Default::default() })
+            .with_override(
+                200,
+                PaletteOverride { material: None, transparency: Some(0.25), reflectivity: Some(0.5) },
+            )
+    }
+
+    #[test]
+    fn round_trip_bytes() {
+        let world = sample_world();
+        let bytes = world.to_bytes().unwrap();
+        assert_eq!(VoxelWorld::from_bytes(&bytes).unwrap(), world);
+    }
+
+    #[test]
+    fn round_trip_file() {
+        let world = sample_world();
+        let path = std::env::temp_dir().join(format!("raytracer-voxel-file-{}.rvx", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        world.save(&path).unwrap();
+        let loaded = VoxelWorld::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), world);
+    }
+
+    #[test]
+    fn round_trip_empty_and_custom_palette() {
+        let empty = VoxelWorld::new(VoxelGrid::new([0, 0, 0], [0, 0, 0], [[0; 4]; 256]));
+        assert_eq!(VoxelWorld::from_bytes(&empty.to_bytes().unwrap()).unwrap(), empty);
+
+        let mut palette = [[0; 4]; 256];
+        palette[0] = [1, 2, 3, 4];
+        palette[255] = [10, 20, 30, 128];
+        let mut grid = VoxelGrid::new([5, 1, 1], [0, 0, 0], palette);
+        grid.cells.copy_from_slice(&[255, 255, 0, 0, 255]);
+        let world = VoxelWorld::new(grid);
+        assert_eq!(VoxelWorld::from_bytes(&world.to_bytes().unwrap()).unwrap(), world);
+    }
+
+    #[test]
+    fn large_uniform_world_is_compact() {
+        let mut grid = VoxelGrid::new([256, 64, 256], [0, 0, 0], default_palette());
+        let half = grid.cells.len() / 2;
+        grid.cells[..half].fill(7);
+        let world = VoxelWorld::new(grid);
+        let bytes = world.to_bytes().unwrap();
+        // 4 millones de celdas: la mitad llena, la otra mitad en chunks vacíos que no se guardan
+        assert!(bytes.len() < 8 * 1024, "encoded size {}", bytes.len());
+        assert_eq!(VoxelWorld::from_bytes(&bytes).unwrap(), world);
+    }
+
+    #[test]
+    fn round_trip_empty_override() {
+        let mut grid = VoxelGrid::new([2, 1, 1], [0, 0, 0], [[0; 4]; 256]);
+        grid.cells[0] = 9; // índice sin color en la paleta
+        let world = VoxelWorld::new(grid).with_override(9, PaletteOverride::default());
+        assert_eq!(VoxelWorld::from_bytes(&world.to_bytes().unwrap()).unwrap(), world);
+    }
+
+    #[test]
+    fn varint_round_trip() {
+        for value in [0, 1, 127, 128, 300, 16_383, 16_384, u32::MAX] {
+            let mut out = Vec::new();
+            write_varint(&mut out, value);
+            let mut reader = Reader { bytes: &out, position: 0 };
+            assert_eq!(read_varint(&mut reader).unwrap(), value);
+            assert_eq!(reader.position, out.len());
+        }
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let bytes = sample_world().to_bytes().unwrap();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(VoxelWorld::from_bytes(&bad_magic).unwrap_err().contains("RVOX"));
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 99;
+        assert!(VoxelWorld::from_bytes(&bad_version).unwrap_err().contains("unsupported version 99"));
+
+        let truncated = &bytes[..bytes.len() - 3];
+        assert_eq!(VoxelWorld::from_bytes(truncated).unwrap_err(), "unexpected end of file");
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(VoxelWorld::from_bytes(&trailing).unwrap_err().contains("unexpected bytes"));
+
+        // tamaño en bytes 6..18: u32::MAX en cada eje, sin llegar a reservar la rejilla
+        let mut huge = bytes.clone();
+        huge[6..18].fill(0xff);
+        assert!(VoxelWorld::from_bytes(&huge).unwrap_err().contains("is too large"));
+    }
+
+    #[test]
+    fn rejects_runs_that_do_not_fill_the_chunk() {
+        let mut grid = VoxelGrid::new([2, 1, 1], [0, 0, 0], default_palette());
+        grid.cells[0] = 5;
+        let mut bytes = VoxelWorld::new(grid).to_bytes().unwrap();
+        // última corrida: largo 1, índice 0 -> largo 2
+        let last_run = bytes.len() - 2;
+        bytes[last_run] = 2;
+        let error = VoxelWorld::from_bytes(&bytes).unwrap_err();
+        assert_eq!(error, "chunk (0, 0, 0): runs cover more than 2 cells");
+    }
+}
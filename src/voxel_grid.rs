@@ -0,0 +1,229 @@
+// voxel_grid.rs
+// Rejilla densa de voxeles con paleta (como la de MagicaVoxel): cada celda guarda un
+// índice de paleta y 0 significa vacío. Se convierte en cubos de la escena con `build`.
+use std::collections::HashMap;
+
+use crate::cube::Cube;
+use crate::material::{Material, MaterialId};
+use crate::material_library::{MaterialLibrary, check_range};
+use crate::math::Vector3;
+use crate::primitive::Primitive;
+
+// RGBA por índice; la entrada 0 no se usa
+pub type Palette = [[u8; 4]; 256];
+
+// Tope de celdas de una rejilla (256 MiB), para no reservar memoria absurda con un archivo
+// corrupto o modelos muy separados
+pub const MAX_CELLS: usize = 1 << 28;
+
+pub(crate) fn check_size(size: [usize; 3]) -> Result<(), String> {
+    let cells = size[0].checked_mul(size[1]).and_then(|n| n.checked_mul(size[2]));
+    match cells {
+        Some(cells) if cells <= MAX_CELLS => Ok(()),
+        _ => Err(format!(
+            "grid size {}x{}x{} is too large (at most {} cells)",
+            size[0], size[1], size[2], MAX_CELLS
+        )),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoxelGrid {
+    pub size: [usize; 3],            // celdas en x, y, z
+    pub origin: [i32; 3],            // coordenada entera de la celda (0, 0, 0)
+    pub cells: Vec<u8>,              // x varía más rápido, luego z, luego y (capas de abajo arriba)
+    pub palette: Palette,
+}
+
+// Cambios por índice de paleta al convertir en materiales
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PaletteOverride {
+    pub material: Option<String>,    // usa un material de la biblioteca en vez del color
+    pub transparency: Option<f32>,
+    pub reflectivity: Option<f32>,
+}
+
+impl VoxelGrid {
+    pub fn new(size: [usize; 3], origin: [i32; 3], palette: Palette) -> Self {
+        VoxelGrid {
+            size,
+            origin,
+            cells: vec![0; size[0] * size[1] * size[2]],
+            palette,
+        }
+    }
+
+    // Rejilla justa para contener los voxeles dados (coordenadas enteras del mundo)
+    pub fn from_voxels(voxels: &[([i32; 3], u8)], palette: Palette) -> Result<Self, String> {
+        if voxels.is_empty() {
+            return Ok(VoxelGrid::new([0, 0, 0], [0, 0, 0], palette));
+        }
+        let mut min = [i32::MAX; 3];
+        let mut max = [i32::MIN; 3];
+        for (position, _) in voxels {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(position[axis]);
+                max[axis] = max[axis].max(position[axis]);
+            }
+        }
+        // en i64 la extensión no desborda aunque los extremos estén en ±2^31
+        let size = [0, 1, 2].map(|axis| (max[axis] as i64 - min[axis] as i64 + 1) as usize);
+        check_size(size)?;
+        let mut grid = VoxelGrid::new(size, min, palette);
+        for (position, index) in voxels {
+            grid.set(*position, *index);
+        }
+        Ok(grid)
+    }
+
+    fn cell_index(&self, position: [i32; 3]) -> Option<usize> {
+        let mut local = [0usize; 3];
+        for axis in 0..3 {
+            let offset = position[axis] - self.origin[axis];
+            if offset < 0 || offset as usize >= self.size[axis] {
+                return None;
+            }
+            local[axis] = offset as usize;
+        }
+        Some((local[1] * self.size[2] + local[2]) * self.size[0] + local[0])
+    }
+
+    // Índice de paleta en una coordenada del mundo; 0 fuera de la rejilla
+    pub fn get(&self, position: [i32; 3]) -> u8 {
+        self.cell_index(position).map_or(0, |i| self.cells[i])
+    }
+
+    // Las coordenadas fuera de la rejilla se ignoran
+    pub fn set(&mut self, position: [i32; 3], index: u8) {
+        if let Some(i) = self.cell_index(position) {
+            self.cells[i] = index;
+        }
+    }
+
+    pub fn voxel_count(&self) -> usize {
+        self.cells.iter().filter(|&&index| index != 0).count()
+    }
+
+    // Voxeles ocupados con su coordenada del mundo, en el orden de `cells`
+    pub fn voxels(&self) -> impl Iterator<Item = ([i32; 3], u8)> + '_ {
+        let [size_x, _, size_z] = self.size;
+        self.cells.iter().enumerate().filter(|(_, index)| **index != 0).map(move |(i, index)| {
+            let x = i % size_x;
+            let z = (i / size_x) % size_z;
+            let y = i / (size_x * size_z);
+            let position = [
+                self.origin[0] + x as i32,
+                self.origin[1] + y as i32,
+                self.origin[2] + z as i32,
+            ];
+            (position, *index)
+        })
+    }
+
+    // Un cubo de lado `scale` por voxel. Cada índice de paleta usado se vuelve un material
+    // "<name>#<índice>" en la biblioteca (salvo que un override lo cambie por otro)
+    pub fn build(
+        &self,
+        name: &str,
+        materials: &mut MaterialLibrary,
+        overrides: &HashMap<u8, PaletteOverride>,
+        scale: f32,
+        offset: Vector3,
+    ) -> Result<Vec<Primitive>, String> {
+        let mut ids: [Option<MaterialId>; 256] = [None; 256];
+        let mut objects = Vec::with_capacity(self.voxel_count());
+
+        for (position, index) in self.voxels() {
+            let material_id = match ids[index as usize] {
+                Some(id) => id,
+                None => {
+                    let id = self.palette_material(name, index, materials, overrides.get(&index))?;
+                    ids[index as usize] = Some(id);
+                    id
+                }
+            };
+            let center = offset
+                + Vector3::new(
+                    position[0] as f32 + 0.5,
+                    position[1] as f32 + 0.5,
+                    position[2] as f32 + 0.5,
+                ) * scale;
+            objects.push(Cube { center, size: scale, material_id }.into());
+        }
+        Ok(objects)
+    }
+
+    fn palette_material(
+        &self,
+        name: &str,
+        index: u8,
+        materials: &mut MaterialLibrary,
+        palette_override: Option<&PaletteOverride>,
+    ) -> Result<MaterialId, String> {
+        let field_err = |field: &str, message: String| format!("palette index {}: field '{}' {}", index, field, message);
+        let mut material = palette_color_material(self.palette[index as usize]);
+
+        if let Some(palette_override) = palette_override {
+            if let Some(library_name) = &palette_override.material {
+                material = materials
+                    .get(library_name)
+                    .ok_or_else(|| field_err("material", format!("names unknown material '{}'", library_name)))?;
+                // sin más cambios se comparte el material de la biblioteca tal cual
+                if palette_override.transparency.is_none() && palette_override.reflectivity.is_none() {
+                    return Ok(materials.id(library_name).unwrap_or_default());
+                }
+            }
+            if let Some(v) = palette_override.transparency {
+                check_range(v, 0.0, 1.0).map_err(|m| field_err("transparency", m))?;
+                material.transparency = v;
+            }
+            if let Some(v) = palette_override.reflectivity {
+                check_range(v, 0.0, 1.0).map_err(|m| field_err("reflectivity", m))?;
+                material.reflectivity = v;
+            }
+            // pedir transparencia o reflejo por índice es pedir los rayos que los muestran
+            if palette_override.transparency.is_some() || palette_override.reflectivity.is_some() {
+                material.secondary_rays = true;
+            }
+            if material.transparency + material.reflectivity > 1.0 {
+                return Err(field_err(
+                    "reflectivity",
+                    format!(
+                        "plus transparency must not exceed 1 (got {} + {})",
+                        material.reflectivity, material.transparency
+                    ),
+                ));
+            }
+        }
+
+        materials.insert(&format!("{}#{}", name, index), material)
+    }
+}
+
+// Material difuso mate con el color de la paleta; el alfa se vuelve transparencia
+fn palette_color_material(rgba: [u8; 4]) -> Material {
+    let diffuse = Vector3::new(rgba[0] as f32, rgba[1] as f32, rgba[2] as f32) / 255.0;
+    let mut material = Material::new(diffuse, [0.9, 0.1], 10.0);
+    material.transparency = 1.0 - rgba[3] as f32 / 255.0;
+    material.secondary_rays = material.transparency > 0.0;
+    material
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vox::default_palette;
+
+    #[test]
+    fn optical_overrides_opt_into_secondary_rays() {
+        let grid = VoxelGrid::from_voxels(&[([0, 0, 0], 1), ([1, 0, 0], 2)], default_palette()).unwrap();
+        let overrides = HashMap::from([(2, PaletteOverride { reflectivity: Some(0.5), ..Default::default() })]);
+        let mut materials = MaterialLibrary::new();
+        grid.build("model", &mut materials, &overrides, 1.0, Vector3::zero()).unwrap();
+
+        assert!(!materials.get("model#1").unwrap().traces_secondary_rays());
+        let mirror = materials.get("model#2").unwrap();
+        assert_eq!(mirror.reflectivity, 0.5);
+        assert!(mirror.traces_secondary_rays());
+    }
+}
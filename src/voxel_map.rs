@@ -0,0 +1,278 @@
+// voxel_map.rs
+// Mapas de voxeles dibujados como texto: cada carácter es una columna del terreno y la
+// leyenda dice qué bloques lleva (material por altura) y si tiene un prefab encima.
+//
+//     tile = 1.0
+//
+//     [legend]
+//     B = { blocks = [[0, "water"]] }
+//     G = { blocks = [[0, "grass"], [1, "grass_dark"]] }
+//     t = { blocks = [[0, "grass"]], prefab = "tree", rotation = 90, mirror = "x" }
+//
+//     [[layers]]
+//     level = 0                 # altura (en bloques) que suma la capa a sus columnas
+//     rows = """
+//     GGBt
+//     GB.g
+//     """
+//
+// Filas = eje z, columnas = eje x; el mapa queda centrado en el origen y la cara superior
+// del nivel 0 en y = 0. Espacio y '.' son columnas vacías. Los prefabs se buscan por
+// nombre entre los que carga la escena (ver prefab.rs).
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::cube::Cube;
+use crate::material::MaterialId;
+use crate::material_library::MaterialLibrary;
+use crate::math::Vector3;
+use crate::prefab::{Placement, Prefab};
+use crate::primitive::Primitive;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MapDef {
+    pub tile: Option<f32>,
+    pub legend: HashMap<String, ColumnDef>,
+    pub layers: Vec<LayerDef>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnDef {
+    #[serde(default)]
+    pub blocks: Vec<(i32, String)>, // (altura, material)
+    pub prefab: Option<String>,
+    pub rotation: Option<i32>,      // grados, múltiplo de 90
+    pub mirror: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LayerDef {
+    #[serde(default)]
+    pub level: i32,
+    pub rows: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ColumnRecipe {
+    pub blocks: Vec<(i32, String)>,
+    pub prefab: Option<String>,
+    pub placement: Placement,       // giro y espejo del prefab; la posición sale del mapa
+}
+
+#[derive(Debug, Clone)]
+pub struct MapLayer {
+    pub level: i32,
+    pub rows: Vec<Vec<char>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VoxelMap {
+    pub tile: f32,
+    pub legend: HashMap<char, ColumnRecipe>,
+    pub layers: Vec<MapLayer>,
+}
+
+impl VoxelMap {
+    pub fn load(file_path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(file_path)
+            .map_err(|e| format!("{}: {}", file_path, e))?;
+        Self::from_toml_str(&source).map_err(|e| format!("{}: {}", file_path, e))
+    }
+
+    pub fn from_toml_str(source: &str) -> Result<Self, String> {
+        let def: MapDef = toml::from_str(source).map_err(|e| e.to_string())?;
+        Self::from_def(def)
+    }
+
+    pub fn from_def(def: MapDef) -> Result<Self, String> {
+        let tile = def.tile.unwrap_or(1.0);
+        if !(tile > 0.0 && tile.is_finite()) {
+            return Err(format!("field 'tile' must be positive (got {})", tile));
+        }
+
+        let mut legend = HashMap::new();
+        for (key, column) in def.legend {
+            let mut chars = key.chars();
+            let ch = match (chars.next(), chars.next()) {
+                (Some(ch), None) if !is_empty_cell(ch) => ch,
+                _ => return Err(format!("legend key \"{}\" must be a single character other than ' ' or '.'", key)),
+            };
+            let mut placement = Placement::at(Vector3::zero())
+                .with_scale(tile)
+                .with_rotation(column.rotation.unwrap_or(0))
+                .map_err(|e| format!("legend '{}': {}", ch, e))?;
+            if let Some(mirror) = &column.mirror {
+                placement = placement.with_mirror(mirror).map_err(|e| format!("legend '{}': {}", ch, e))?;
+            }
+            legend.insert(ch, ColumnRecipe { blocks: column.blocks, prefab: column.prefab, placement });
+        }
+
+        let mut layers = Vec::with_capacity(def.layers.len());
+        for (i, layer) in def.layers.into_iter().enumerate() {
+            let rows: Vec<Vec<char>> = layer.rows.lines().map(|line| line.trim_end().chars().collect()).collect();
+            for (r, row) in rows.iter().enumerate() {
+                for (c, ch) in row.iter().enumerate() {
+                    if !is_empty_cell(*ch) && !legend.contains_key(ch) {
+                        return Err(format!(
+                            "layer {}, row {}, column {}: character '{}' is not in the legend",
+                            i, r + 1, c + 1, ch
+                        ));
+                    }
+                }
+            }
+            layers.push(MapLayer { level: layer.level, rows });
+        }
+
+        Ok(VoxelMap { tile, legend, layers })
+    }
+
+    // Columnas (x) y filas (z) de la capa más grande; todas las capas comparten el centro
+    pub fn size(&self) -> (usize, usize) {
+        let cols = self.layers.iter().flat_map(|l| l.rows.iter()).map(|row| row.len()).max().unwrap_or(0);
+        let rows = self.layers.iter().map(|l| l.rows.len()).max().unwrap_or(0);
+        (cols, rows)
+    }
+
+    // Convierte el mapa en cubos; `offset` mueve todo el mapa en el mundo
+    pub fn build(
+        &self,
+        materials: &MaterialLibrary,
+        prefabs: &HashMap<String, Prefab>,
+        offset: Vector3,
+    ) -> Result<Vec<Primitive>, String> {
+        let tile = self.tile;
+        let (cols, rows) = self.size();
+        let x0 = -((cols as f32 - 1.0) * 0.5) * tile + offset.x;
+        let z0 = -((rows as f32 - 1.0) * 0.5) * tile + offset.z;
+        let y0 = -tile * 0.5 + offset.y; // centro de los bloques del nivel 0
+
+        let material_id = |name: &str, ch: char| -> Result<MaterialId, String> {
+            materials
+                .id(name)
+                .ok_or_else(|| format!("legend '{}': unknown material '{}'", ch, name))
+        };
+
+        let mut objects = Vec::new();
+        for layer in &self.layers {
+            for (r, row) in layer.rows.iter().enumerate() {
+                for (c, ch) in row.iter().enumerate() {
+                    let Some(recipe) = self.legend.get(ch) else {
+                        continue;
+                    };
+                    let x = x0 + c as f32 * tile;
+                    let z = z0 + r as f32 * tile;
+
+                    for (height, material) in &recipe.blocks {
+                        let y = y0 + (layer.level + height) as f32 * tile;
+                        let material_id = material_id(material, *ch)?;
+                        objects.push(Cube { center: Vector3::new(x, y, z), size: tile, material_id }.into());
+                    }
+
+                    if let Some(name) = &recipe.prefab {
+                        let prefab = prefabs
+                            .get(name)
+                            .ok_or_else(|| format!("legend '{}': unknown prefab '{}'", ch, name))?;
+                        let top = recipe.blocks.iter().map(|(h, _)| *h).max().unwrap_or(0);
+                        let placement = Placement {
+                            position: Vector3::new(x, y0 + (layer.level + top) as f32 * tile, z),
+                            ..recipe.placement
+                        };
+                        prefab
+                            .place(&placement, materials, &mut objects)
+                            .map_err(|e| format!("prefab '{}': {}", name, e))?;
+                    }
+                }
+            }
+        }
+        Ok(objects)
+    }
+}
+
+fn is_empty_cell(ch: char) -> bool {
+    ch == ' ' || ch == '.'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::TextureManager;
+
+    // Grilla y árboles escritos a mano en el `main` original, antes de mapas y prefabs
+    const LEGACY_GRID: [&str; 8] = ["GGBtGG", "GGBgGG", "GGBggG", "GGBBgT", "GTBBgG", "GGgBgg", "GGgBBg", "tggBBg"];
+    const SMALL_CANOPY: [&[&str]; 2] = [&["010", "111", "010"], &["000", "010", "000"]];
+    const TALL_CANOPY: [&[&str]; 3] = [
+        &["111", "111", "111", "010"],
+        &["010", "111", "010", "000"],
+        &["000", "010", "000", "000"],
+    ];
+
+    type CubeKey = (i32, i32, i32, i32, MaterialId);
+
+    fn key(center: Vector3, size: f32, material_id: MaterialId) -> CubeKey {
+        let q = |v: f32| (v * 100.0).round() as i32;
+        (q(center.x), q(center.y), q(center.z), q(size), material_id)
+    }
+
+    fn legacy_trees(wood: MaterialId, leaves: MaterialId) -> Vec<CubeKey> {
+        let mut cubes = Vec::new();
+        for (r, row) in LEGACY_GRID.iter().enumerate() {
+            for (c, ch) in row.chars().enumerate() {
+                let (floor, trunks, canopy): (f32, usize, &[&[&str]]) = match ch {
+                    't' => (-0.5, 2, &SMALL_CANOPY),
+                    'T' => (0.5, 3, &TALL_CANOPY),
+                    _ => continue,
+                };
+                let (x, z) = (c as f32 - 2.5, r as f32 - 3.5);
+                for i in 0..trunks {
+                    cubes.push(key(Vector3::new(x, floor + 0.45 + 0.9 * i as f32, z), 0.9, wood));
+                }
+                let top = floor + 0.9 * trunks as f32;
+                for (level, mask) in canopy.iter().enumerate() {
+                    for (lr, mask_row) in mask.iter().enumerate() {
+                        for (lc, cell) in mask_row.chars().enumerate() {
+                            if cell == '1' {
+                                let center = Vector3::new(x + lc as f32 - 1.0, top + 0.5 + level as f32, z + lr as f32 - 1.5);
+                                cubes.push(key(center, 1.0, leaves));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        cubes.sort();
+        cubes
+    }
+
+    #[test]
+    fn diorama_trees_match_the_original_hand_built_ones() {
+        let materials = MaterialLibrary::load("assets/materials.toml", &mut TextureManager::new()).unwrap();
+        let prefabs = HashMap::from([
+            ("tree".to_string(), Prefab::load("assets/prefabs/tree.toml").unwrap()),
+            ("tall_tree".to_string(), Prefab::load("assets/prefabs/tall_tree.toml").unwrap()),
+        ]);
+        let map = VoxelMap::load("assets/maps/diorama.toml").unwrap();
+        let (wood, leaves) = (materials.id("wood").unwrap(), materials.id("leaves").unwrap());
+
+        let mut trees: Vec<CubeKey> = map
+            .build(&materials, &prefabs, Vector3::zero())
+            .unwrap()
+            .iter()
+            .filter_map(|object| match object {
+                Primitive::Cube(cube) if cube.material_id == wood || cube.material_id == leaves => {
+                    Some(key(cube.center, cube.size, cube.material_id))
+                }
+                _ => None,
+            })
+            .collect();
+        trees.sort();
+
+        // el 't' de la primera fila: tronco en y = -0.05 y 0.85, copa corrida z = fila - 1.5
+        assert!(trees.contains(&key(Vector3::new(0.5, -0.05, -3.5), 0.9, wood)));
+        assert!(trees.contains(&key(Vector3::new(0.5, 0.85, -3.5), 0.9, wood)));
+        assert!(trees.contains(&key(Vector3::new(0.5, 1.8, -5.0), 1.0, leaves)));
+        assert_eq!(trees, legacy_trees(wood, leaves));
+    }
+}
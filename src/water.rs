@@ -0,0 +1,65 @@
+// water.rs
+use crate::math::Vector3;
+
+const GRAVITY: f32 = 9.81;
+
+// Olas tipo Gerstner sumadas: solo se usan sus derivadas para inclinar la normal,
+// la geometría de los cubos no cambia
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaterWaves {
+    pub amplitude: f32,              // altura de la ola principal
+    pub wavelength: f32,             // longitud de onda de la ola principal
+    pub speed: f32,                  // multiplicador de la velocidad de fase
+    pub direction: f32,              // rumbo de la ola principal en el plano xz (radianes)
+}
+
+// Cada componente: (escala de longitud de onda, escala de amplitud, giro de la dirección)
+const WAVE_COMPONENTS: [(f32, f32, f32); 4] = [
+    (1.00, 1.00, 0.0),
+    (0.61, 0.55, 0.7),
+    (0.37, 0.30, -1.1),
+    (0.23, 0.18, 2.3),
+];
+
+impl WaterWaves {
+    pub fn new(amplitude: f32, wavelength: f32, speed: f32) -> Self {
+        WaterWaves {
+            amplitude,
+            wavelength,
+            speed,
+            direction: 0.0,
+        }
+    }
+
+    pub fn with_direction(mut self, direction: f32) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    // Gradiente (dh/dx, dh/dz) de la superficie en el punto e instante dados
+    pub fn slope(&self, point: Vector3, time: f32) -> (f32, f32) {
+        let mut dx = 0.0;
+        let mut dz = 0.0;
+        for (wavelength_scale, amplitude_scale, turn) in WAVE_COMPONENTS {
+            let wavelength = (self.wavelength * wavelength_scale).max(1e-3);
+            let amplitude = self.amplitude * amplitude_scale;
+            let k = 2.0 * std::f32::consts::PI / wavelength;
+            let omega = (GRAVITY * k).sqrt() * self.speed; // dispersión de aguas profundas
+            let (dir_z, dir_x) = (self.direction + turn).sin_cos();
+
+            let phase = k * (dir_x * point.x + dir_z * point.z) - omega * time;
+            let derivative = amplitude * k * phase.cos();
+            dx += derivative * dir_x;
+            dz += derivative * dir_z;
+        }
+        (dx, dz)
+    }
+
+    // Inclina `normal` con la pendiente de las olas proyectada sobre el plano de la cara
+    pub fn perturb_normal(&self, point: Vector3, normal: Vector3, time: f32) -> Vector3 {
+        let (dx, dz) = self.slope(point, time);
+        let gradient = Vector3::new(dx, 0.0, dz);
+        let tangential = gradient - normal * gradient.dot(normal);
+        (normal - tangential).normalized()
+    }
+}